@@ -0,0 +1,90 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, Result, Type};
+
+/// Scalars whose arrays are a single vector attribute, e.g. `[f32; 3]` is a `vec3`.
+const VECTOR_SCALARS: [&str; 9] = ["f32", "f64", "i32", "u32", "i16", "u16", "i8", "u8", "f16"];
+
+/// Returns true for the scalars, also when written with a path such as `half::f16` or `core::primitive::u8`.
+fn is_scalar(path: &syn::TypePath) -> bool {
+    path.path
+        .segments
+        .last()
+        .is_some_and(|segment| VECTOR_SCALARS.iter().any(|scalar| segment.ident == scalar))
+}
+
+/// Returns the element type and length if the field is an array of attributes rather than a vector.
+fn attribute_array(ty: &Type) -> Option<(&Type, &syn::Expr)> {
+    match ty {
+        Type::Array(array) => match &*array.elem {
+            Type::Path(path) if is_scalar(path) => None,
+            elem => Some((elem, &array.len)),
+        },
+        _ => None,
+    }
+}
+
+pub fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+            Fields::Unnamed(fields) => fields.unnamed.iter().collect::<Vec<_>>(),
+            Fields::Unit => Vec::new(),
+        },
+        _ => return Err(Error::new_spanned(input, "BufferData can only be derived for structs")),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let module = quote!(::simple_gl::storage::buffer);
+
+    let attributes = fields.iter().enumerate().map(|(i, field)| {
+        let member = match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = syn::Index::from(i);
+                quote!(#index)
+            }
+        };
+        let offset = quote!(::core::mem::offset_of!(Self, #member) as u32);
+
+        match attribute_array(&field.ty) {
+            Some((elem, len)) => quote! {
+                for i in 0..(#len) {
+                    let offset = #offset + (i * ::core::mem::size_of::<#elem>()) as u32;
+                    layout.extend(<#elem as #module::BufferData>::layout().into_iter().map(|attribute| #module::Attribute {
+                        offset: attribute.offset + offset,
+                        ..attribute
+                    }));
+                }
+            },
+            None => {
+                let ty = &field.ty;
+                quote! {
+                    let offset = #offset;
+                    layout.extend(<#ty as #module::BufferData>::layout().into_iter().map(|attribute| #module::Attribute {
+                        offset: attribute.offset + offset,
+                        ..attribute
+                    }));
+                }
+            },
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #module::BufferData for #name #ty_generics #where_clause {
+            fn prototype() -> Vec<(#module::Primitive, u32)> {
+                #module::packed_prototype(
+                    &<Self as #module::BufferData>::layout(),
+                    ::core::mem::size_of::<Self>() as u32
+                )
+            }
+
+            fn layout() -> Vec<#module::Attribute> {
+                let mut layout = Vec::new();
+                #(#attributes)*
+                layout
+            }
+        }
+    })
+}
@@ -0,0 +1,163 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Fields, LitInt, Result};
+
+pub enum Layout {
+    Std140,
+    Std430,
+}
+
+impl Layout {
+    fn name(&self) -> &'static str {
+        match self {
+            Layout::Std140 => "std140",
+            Layout::Std430 => "std430",
+        }
+    }
+
+    fn trait_name(&self) -> &'static str {
+        match self {
+            Layout::Std140 => "Std140",
+            Layout::Std430 => "Std430",
+        }
+    }
+
+    /// The minimum alignment of a struct, std140 rounds structs up to a vec4.
+    fn struct_align(&self) -> usize {
+        match self {
+            Layout::Std140 => 16,
+            Layout::Std430 => 1,
+        }
+    }
+}
+
+/// Reads `#[std140(size = N)]` from the struct attributes.
+fn expected_size(input: &DeriveInput, layout: &Layout) -> Result<Option<LitInt>> {
+    let mut size = None;
+    for attr in &input.attrs {
+        if !attr.path().is_ident(layout.name()) {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("size") {
+                size = Some(meta.value()?.parse::<LitInt>()?);
+                Ok(())
+            } else {
+                Err(meta.error(format!("unknown {} attribute", layout.name())))
+            }
+        })?;
+    }
+    Ok(size)
+}
+
+pub fn derive(input: &DeriveInput, layout: Layout) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+            Fields::Unnamed(fields) => fields.unnamed.iter().collect::<Vec<_>>(),
+            Fields::Unit => Vec::new(),
+        },
+        _ => return Err(Error::new_spanned(input, format!("{} can only be derived for structs", layout.trait_name()))),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let trait_name = format_ident!("{}", layout.trait_name());
+    let write = format_ident!("write_{}", layout.name());
+    let module = quote!(::simple_gl::storage::buffer);
+    let struct_align = layout.struct_align();
+
+    let types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+    let members: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = syn::Index::from(i);
+                quote!(#index)
+            }
+        })
+        .collect();
+
+    let size_check = match expected_size(input, &layout)? {
+        Some(size) => {
+            if !input.generics.params.is_empty() {
+                return Err(Error::new_spanned(size, "the size can only be checked on structs without generics"));
+            }
+            let message = format!("{} size of {} does not match the declared size", layout.name(), name);
+            quote! {
+                const _: () = assert!(<#name as #module::#trait_name>::SIZE == #size, #message);
+            }
+        },
+        None => quote!(),
+    };
+
+    let write = match layout {
+        Layout::Std140 => quote! {
+            fn #write(&self, out: &mut [u8]) {
+                let offset = 0;
+                #(
+                    let offset = #module::align_to(offset, <#types as #module::#trait_name>::ALIGN);
+                    let end = offset + <#types as #module::#trait_name>::SIZE;
+                    #module::#trait_name::#write(&self.#members, &mut out[offset..end]);
+                    let offset = end;
+                )*
+                let _ = offset;
+            }
+        },
+        Layout::Std430 => quote!(),
+    };
+
+    // std430 values are uploaded as they are, so the Rust layout has to match field by field
+    let layout_check = match layout {
+        Layout::Std430 => {
+            let offset_message = format!("{} is not laid out as std430, a field offset differs", name);
+            let size_message = format!("{} is not laid out as std430, a field size differs", name);
+            let struct_message = format!("{} is not laid out as std430, add explicit padding to the end of the struct", name);
+            quote! {
+                const LAYOUT: () = {
+                    let offset = 0;
+                    #(
+                        let offset = #module::align_to(offset, <#types as #module::#trait_name>::ALIGN);
+                        assert!(::core::mem::offset_of!(Self, #members) == offset, #offset_message);
+                        assert!(::core::mem::size_of::<#types>() == <#types as #module::#trait_name>::SIZE, #size_message);
+                        let offset = offset + <#types as #module::#trait_name>::SIZE;
+                    )*
+                    let _ = offset;
+                    assert!(::core::mem::size_of::<Self>() == <Self as #module::#trait_name>::SIZE, #struct_message);
+                };
+            }
+        },
+        Layout::Std140 => quote!(),
+    };
+
+    // Generic structs are checked for each element type when a buffer of them is bound
+    let layout_assert = match layout {
+        Layout::Std430 if input.generics.params.is_empty() => quote! {
+            const _: () = <#name as #module::#trait_name>::LAYOUT;
+        },
+        _ => quote!(),
+    };
+
+    Ok(quote! {
+        impl #impl_generics #module::#trait_name for #name #ty_generics #where_clause {
+            const ALIGN: usize = {
+                let align = #struct_align;
+                #( let align = #module::max_align(align, <#types as #module::#trait_name>::ALIGN); )*
+                #module::align_to(align, #struct_align)
+            };
+            const SIZE: usize = {
+                let offset = 0;
+                #( let offset = #module::align_to(offset, <#types as #module::#trait_name>::ALIGN) + <#types as #module::#trait_name>::SIZE; )*
+                #module::align_to(offset, <Self as #module::#trait_name>::ALIGN)
+            };
+
+            #write
+            #layout_check
+        }
+
+        #size_check
+        #layout_assert
+    })
+}
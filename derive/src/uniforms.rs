@@ -0,0 +1,110 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, LitStr, Result, Type};
+
+/// Scalars whose arrays are GLSL vectors rather than GLSL arrays, e.g. `[f32; 3]` is a `vec3`.
+const VECTOR_SCALARS: [&str; 5] = ["f32", "f64", "i32", "u32", "bool"];
+
+#[derive(Default)]
+struct FieldOptions {
+    name: Option<LitStr>,
+    skip: bool,
+    array: bool,
+}
+
+/// Reads `#[uniform(name = "..", skip, array)]` from the field attributes.
+fn field_options(field: &syn::Field) -> Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in &field.attrs {
+        if !attr.path().is_ident("uniform") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                options.name = Some(meta.value()?.parse::<LitStr>()?);
+            } else if meta.path.is_ident("skip") {
+                options.skip = true;
+            } else if meta.path.is_ident("array") {
+                options.array = true;
+            } else {
+                return Err(meta.error("unknown uniform attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+/// Returns true if the type is one of `VECTOR_SCALARS`, written with or without a path.
+fn is_scalar(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| VECTOR_SCALARS.iter().any(|scalar| segment.ident == scalar)),
+        _ => false,
+    }
+}
+
+/// Returns true if the field is a GLSL array, any Rust array except vectors and matrices of scalars.
+fn is_array(ty: &Type) -> bool {
+    match ty {
+        Type::Array(array) => match &*array.elem {
+            Type::Array(column) => !is_scalar(&column.elem),
+            elem => !is_scalar(elem),
+        },
+        _ => false,
+    }
+}
+
+pub fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(input, "Uniforms can only be derived for structs with named fields")),
+        },
+        _ => return Err(Error::new_spanned(input, "Uniforms can only be derived for structs")),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let module = quote!(::simple_gl::graphics);
+
+    let mut sets = Vec::new();
+    for field in fields {
+        let options = field_options(field)?;
+        if options.skip {
+            continue;
+        }
+
+        let ident = field.ident.as_ref().unwrap();
+        let uniform = match options.name {
+            Some(name) => name.value(),
+            None => ident.to_string(),
+        };
+        let value = if options.array || is_array(&field.ty) {
+            quote!(&self.#ident[..])
+        } else {
+            quote!(&self.#ident)
+        };
+
+        sets.push(quote! {
+            #module::Uniforms::set_uniforms(#value, setter, &format!("{}{}", prefix, #uniform));
+        });
+    }
+
+    Ok(quote! {
+        impl #impl_generics #module::Uniforms for #name #ty_generics #where_clause {
+            fn set_uniforms(&self, setter: &mut #module::UniformSetter, name: &str) {
+                // The top level struct has no name, nested structs are members of their field
+                let prefix = if name.is_empty() {
+                    String::new()
+                } else {
+                    format!("{}.", name)
+                };
+                #(#sets)*
+            }
+        }
+    })
+}
@@ -3,7 +3,7 @@
 in VertexData {
     vec2 position;
     flat vec4 Amount;
-    flat bool right;
+    flat int right;
 } vertex;
 
 uniform float margin;
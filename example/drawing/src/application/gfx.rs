@@ -1,32 +1,22 @@
 use anyhow::Result;
 
-use std::rc::Rc;
-use std::include_str;
+use std::path::Path;
 
 pub use simple_gl::graphics::*;
 
 pub struct Graphics {
-    pub program: Program
+    pub program: ReloadableProgram
 }
 
 impl Graphics {
     pub fn new() -> Result<Graphics> {
+        let shaders = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/shaders/cube");
 
-        let vert_shader = VertexShader::from_source(
-            include_str!("../../resources/shaders/cube/cube.vert")
-        )?;
-        let geom_shader = GeometryShader::from_source(
-            include_str!("../../resources/shaders/cube/cube.geom")
-        )?;
-        let frag_shader = FragmentShader::from_source(
-            include_str!("../../resources/shaders/cube/cube.frag")
-        )?;
-
-        let mut program = Program::from_shaders(vec![
-            Rc::new(vert_shader),
-            Rc::new(geom_shader),
-            Rc::new(frag_shader),
-        ])?;
+        let mut program = ReloadableProgram::new()?;
+        program.add_stage::<Vertex, _>(shaders.join("cube.vert"))?;
+        program.add_stage::<Geometry, _>(shaders.join("cube.geom"))?;
+        program.add_stage::<Fragment, _>(shaders.join("cube.frag"))?;
+        program.build()?;
         program.set_used();
 
         program.set_uniform("margin", 0.5f32);
@@ -134,6 +134,13 @@ fn main() -> Result<()> {
             }
         }
         
+        // RELOAD CHANGED SHADERS
+        match gfx.program.reload() {
+            Ok(true) => gfx.program.set_uniform("margin", margin),
+            Ok(false) => {},
+            Err(e) => println!("{}", e)
+        }
+
         unsafe {
             gl::Clear(gl::COLOR_BUFFER_BIT);
         }
@@ -1,57 +1,356 @@
-use anyhow::{Error, anyhow};
 use gl::types::*;
-use std::ffi::CString;
+use std::fmt;
 
-fn get_error<Len, Log>(get_len: Len, get_log: Log) -> Error 
+fn get_log<Len, Log>(get_len: Len, get_log: Log) -> String
     where
         Len: FnOnce(&mut GLint),
-        Log: FnOnce(GLint, *mut gl::types::GLchar)
+        Log: FnOnce(GLint, &mut GLsizei, *mut gl::types::GLchar)
 {
     let mut len: GLint = 0;
     get_len(&mut len);
+    if len <= 0 {
+        return String::new();
+    }
 
-    // allocate buffer of correct size
-    let mut buffer: Vec<u8> = Vec::with_capacity(len as usize + 1);
-    // fill it with len spaces
-    buffer.extend([b' '].iter().cycle().take(len as usize));
-    // convert buffer to CString
-    let error: CString = unsafe { CString::from_vec_unchecked(buffer) };
+    // allocate buffer of correct size, the log is written with a null terminator
+    let mut buffer: Vec<u8> = vec![0; len as usize];
+    let mut written: GLsizei = 0;
+    get_log(len, &mut written, buffer.as_mut_ptr() as *mut gl::types::GLchar);
+    buffer.truncate(written.clamp(0, len) as usize);
 
-    get_log(len, error.as_ptr() as *mut gl::types::GLchar);
-    match error.into_string() {
-        Ok(msg) => anyhow!(msg),
-        Err(e) => Error::from(e)
-    }
+    String::from_utf8_lossy(&buffer).trim_end_matches('\0').to_owned()
 }
 
-pub fn get_program_error(id: GLuint) -> Error {
-    get_error(
+pub fn get_program_log(id: GLuint) -> String {
+    get_log(
         |len| unsafe {
             gl::GetProgramiv(id, gl::INFO_LOG_LENGTH, len);
         },
-        |len, log| unsafe {
+        |len, written, log| unsafe {
             gl::GetProgramInfoLog(
                 id,
                 len,
-                std::ptr::null_mut(),
+                written,
                 log
             );
         }
     )
 }
 
-pub fn get_shader_error(id: GLuint) -> Error {
-    get_error(
+pub fn get_shader_log(id: GLuint) -> String {
+    get_log(
         |len| unsafe {
             gl::GetShaderiv(id, gl::INFO_LOG_LENGTH, len);
         },
-        |len, log| unsafe {
+        |len, written, log| unsafe {
             gl::GetShaderInfoLog(
                 id,
                 len,
-                std::ptr::null_mut(),
+                written,
+                log
+            );
+        }
+    )
+}
+
+pub fn get_pipeline_log(id: GLuint) -> String {
+    get_log(
+        |len| unsafe {
+            gl::GetProgramPipelineiv(id, gl::INFO_LOG_LENGTH, len);
+        },
+        |len, written, log| unsafe {
+            gl::GetProgramPipelineInfoLog(
+                id,
+                len,
+                written,
                 log
             );
         }
     )
-}
\ No newline at end of file
+}
+
+/// An error reported by the driver while building a shader or program.
+///
+/// Functions returning `anyhow::Result` wrap this error, so it can be recovered with
+/// `error.downcast_ref::<ShaderError>()`.
+#[derive(Debug, Clone)]
+pub enum ShaderError {
+    Compile {
+        stage: &'static str,
+        log: String,
+        diagnostics: Vec<Diagnostic>,
+    },
+    Link {
+        log: String,
+        diagnostics: Vec<Diagnostic>,
+    },
+    Validation {
+        log: String,
+        diagnostics: Vec<Diagnostic>,
+    },
+}
+
+impl ShaderError {
+    pub fn compile(stage: &'static str, log: String) -> ShaderError {
+        ShaderError::Compile {
+            stage,
+            diagnostics: parse_log(&log),
+            log,
+        }
+    }
+
+    pub fn link(log: String) -> ShaderError {
+        ShaderError::Link {
+            diagnostics: parse_log(&log),
+            log,
+        }
+    }
+
+    pub fn validation(log: String) -> ShaderError {
+        ShaderError::Validation {
+            diagnostics: parse_log(&log),
+            log,
+        }
+    }
+
+    /// Returns the name of the shader stage that failed, if the error is a compile error.
+    pub fn stage(&self) -> Option<&'static str> {
+        match self {
+            ShaderError::Compile { stage, .. } => Some(stage),
+            _ => None,
+        }
+    }
+
+    /// Returns the info log as reported by the driver.
+    pub fn log(&self) -> &str {
+        match self {
+            ShaderError::Compile { log, .. } => log,
+            ShaderError::Link { log, .. } => log,
+            ShaderError::Validation { log, .. } => log,
+        }
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        match self {
+            ShaderError::Compile { diagnostics, .. } => diagnostics,
+            ShaderError::Link { diagnostics, .. } => diagnostics,
+            ShaderError::Validation { diagnostics, .. } => diagnostics,
+        }
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderError::Compile { stage, log, .. } => write!(f, "Failed to compile {} shader: {}", stage, log),
+            ShaderError::Link { log, .. } => write!(f, "Failed to link program: {}", log),
+            ShaderError::Validation { log, .. } => write!(f, "Failed to validate program: {}", log),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    fn parse(word: &str) -> Option<Severity> {
+        match word.trim().to_ascii_lowercase().as_str() {
+            "error" | "fatal error" => Some(Severity::Error),
+            "warning" => Some(Severity::Warning),
+            "info" | "note" => Some(Severity::Info),
+            _ => None,
+        }
+    }
+}
+
+/// A single message from a driver info log.
+///
+/// The source index is the index into the sources given to `Shader::set_sources`,
+/// or the source string number set by a `#line` directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub source: Option<u32>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Parses an info log in the Mesa, NVIDIA or AMD format into diagnostics.
+/// Lines that match no known format are kept as messages without a location.
+pub fn parse_log(log: &str) -> Vec<Diagnostic> {
+    log.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            parse_mesa(line)
+                .or_else(|| parse_nvidia(line))
+                .or_else(|| parse_amd(line))
+                .or_else(|| parse_plain(line))
+                .unwrap_or_else(|| Diagnostic {
+                    source: None,
+                    line: None,
+                    column: None,
+                    severity: Severity::Info,
+                    message: line.to_owned(),
+                })
+        })
+        .collect()
+}
+
+/// Splits a leading decimal number from the text.
+fn number(text: &str) -> Option<(u32, &str)> {
+    let end = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let value = text[..end].parse().ok()?;
+    Some((value, &text[end..]))
+}
+
+/// Splits the severity from `severity: message`.
+fn severity(text: &str) -> Option<(Severity, &str)> {
+    let (word, message) = text.split_at(text.find(':')?);
+    Some((Severity::parse(word)?, message[1..].trim()))
+}
+
+/// `0:12(5): error: message`
+fn parse_mesa(line: &str) -> Option<Diagnostic> {
+    let (source, rest) = number(line)?;
+    let (line_number, rest) = number(rest.strip_prefix(':')?)?;
+    let (column, rest) = number(rest.strip_prefix('(')?)?;
+    let rest = rest.strip_prefix("):")?;
+    let (severity, message) = severity(rest)?;
+
+    Some(Diagnostic {
+        source: Some(source),
+        line: Some(line_number),
+        column: Some(column),
+        severity,
+        message: message.to_owned(),
+    })
+}
+
+/// `0(12) : error C0000: message`
+fn parse_nvidia(line: &str) -> Option<Diagnostic> {
+    let (source, rest) = number(line)?;
+    let (line_number, rest) = number(rest.strip_prefix('(')?)?;
+    let rest = rest.strip_prefix(')')?.trim_start().strip_prefix(':')?.trim_start();
+
+    // The severity is followed by an error code before the colon
+    let (word, rest) = rest.split_at(rest.find(' ')?);
+    let severity = Severity::parse(word)?;
+    let message = match rest.find(':') {
+        Some(colon) => &rest[colon + 1..],
+        None => rest,
+    };
+
+    Some(Diagnostic {
+        source: Some(source),
+        line: Some(line_number),
+        column: None,
+        severity,
+        message: message.trim().to_owned(),
+    })
+}
+
+/// `ERROR: 0:12: message`
+fn parse_amd(line: &str) -> Option<Diagnostic> {
+    let (severity, rest) = severity(line)?;
+    let (source, rest) = number(rest)?;
+    let (line_number, rest) = number(rest.strip_prefix(':')?)?;
+    let message = rest.strip_prefix(':')?;
+
+    Some(Diagnostic {
+        source: Some(source),
+        line: Some(line_number),
+        column: None,
+        severity,
+        message: message.trim().to_owned(),
+    })
+}
+
+/// `error: message`
+fn parse_plain(line: &str) -> Option<Diagnostic> {
+    let (severity, message) = severity(line)?;
+
+    Some(Diagnostic {
+        source: None,
+        line: None,
+        column: None,
+        severity,
+        message: message.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mesa_log() {
+        let log = "0:12(5): error: syntax error, unexpected IDENTIFIER\n0:3(10): warning: `x' used uninitialized\n";
+        let diagnostics = parse_log(log);
+
+        assert_eq!(diagnostics, vec![
+            Diagnostic {
+                source: Some(0),
+                line: Some(12),
+                column: Some(5),
+                severity: Severity::Error,
+                message: "syntax error, unexpected IDENTIFIER".to_owned(),
+            },
+            Diagnostic {
+                source: Some(0),
+                line: Some(3),
+                column: Some(10),
+                severity: Severity::Warning,
+                message: "`x' used uninitialized".to_owned(),
+            },
+        ]);
+    }
+
+    #[test]
+    fn parses_nvidia_log() {
+        let log = "1(27) : error C1008: undefined variable \"colour\"\n0(4) : warning C7050: \"pos\" might be used before being initialized";
+        let diagnostics = parse_log(log);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].source, Some(1));
+        assert_eq!(diagnostics[0].line, Some(27));
+        assert_eq!(diagnostics[0].column, None);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].message, "undefined variable \"colour\"");
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+        assert_eq!(diagnostics[1].line, Some(4));
+    }
+
+    #[test]
+    fn parses_amd_log() {
+        let log = "ERROR: 0:8: 'vec5' : undeclared identifier \nERROR: 1 compilation errors.  No code generated.";
+        let diagnostics = parse_log(log);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].source, Some(0));
+        assert_eq!(diagnostics[0].line, Some(8));
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].message, "'vec5' : undeclared identifier");
+        assert_eq!(diagnostics[1].line, None);
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+        assert_eq!(diagnostics[1].message, "1 compilation errors.  No code generated.");
+    }
+
+    #[test]
+    fn keeps_unknown_lines() {
+        let diagnostics = parse_log("Vertex info\n-----------\nerror: vertex shader output `uv' not consumed");
+
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].severity, Severity::Info);
+        assert_eq!(diagnostics[0].message, "Vertex info");
+        assert_eq!(diagnostics[2].severity, Severity::Error);
+        assert_eq!(diagnostics[2].message, "vertex shader output `uv' not consumed");
+    }
+}
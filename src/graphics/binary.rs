@@ -0,0 +1,213 @@
+use gl::types::*;
+use super::*;
+use anyhow::{Result, anyhow, bail};
+use crate::get_value;
+use std::ffi::CStr;
+use std::path::{Path, PathBuf};
+
+/// Stores linked program binaries in a directory, so programs can skip compiling and linking on later runs.
+///
+/// Binaries are keyed by a hash of the shader stages and sources, the link settings of the program
+/// and the driver vendor, renderer and version, so a driver update results in a cache miss instead
+/// of loading an incompatible binary.
+pub struct ProgramBinaryCache {
+    dir: PathBuf,
+}
+
+impl ProgramBinaryCache {
+    pub fn new<P>(dir: P) -> Result<ProgramBinaryCache>
+        where
+            P: AsRef<Path>
+    {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)
+            .map_err(|e| anyhow!("Failed to create program cache {}: {}", dir.display(), e))?;
+
+        Ok(ProgramBinaryCache {
+            dir: dir.to_owned(),
+        })
+    }
+
+    /// Restores `program` from the cache, given the type and source of each shader stage it is built from.
+    ///
+    /// The link settings of the program, its attribute and output locations and transform feedback varyings,
+    /// have to be set before loading as they are part of the key. Defines have to be injected into the sources.
+    /// If there is no cached binary or the driver rejects it, `attach` is called to attach the shaders
+    /// built from the sources, the program is linked and its binary is written to the cache.
+    /// Failing to write the cache does not fail the load.
+    pub fn load<F>(&self, mut program: Program, stages: &[(GLenum, &str)], attach: F) -> Result<Program>
+    where
+        F: FnOnce(&mut Program) -> Result<()>
+    {
+        let path = self.path(&program, stages);
+
+        if let Ok((format, binary)) = read_binary(&path) {
+            if program.load_binary(format, &binary).is_ok() {
+                return Ok(program);
+            }
+        }
+
+        program.set_binary_retrievable(true);
+        attach(&mut program)?;
+        program.link()?;
+
+        if supports_binaries() {
+            if let Ok((format, binary)) = program.binary() {
+                let _ = write_binary(&path, format, &binary);
+            }
+        }
+        Ok(program)
+    }
+
+    /// Removes the cached binary for the program and stages, if there is one.
+    pub fn remove(&self, program: &Program, stages: &[(GLenum, &str)]) -> Result<()> {
+        let path = self.path(program, stages);
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        Ok(())
+    }
+
+    fn path(&self, program: &Program, stages: &[(GLenum, &str)]) -> PathBuf {
+        let mut settings = Vec::new();
+        program.write_link_settings(&mut settings);
+        let driver: Vec<String> = [gl::VENDOR, gl::RENDERER, gl::VERSION]
+            .iter()
+            .map(|name| driver_string(*name))
+            .collect();
+        self.dir.join(format!("{:016x}.bin", key(stages, &settings, &driver)))
+    }
+}
+
+fn key(stages: &[(GLenum, &str)], settings: &[u8], driver: &[String]) -> u64 {
+    let mut hash = Fnv::new();
+    for (stage, source) in stages {
+        hash.write(&stage.to_le_bytes());
+        hash.write(source.as_bytes());
+        hash.write(&[0]);
+    }
+    hash.write(settings);
+    for name in driver {
+        hash.write(name.as_bytes());
+        hash.write(&[0]);
+    }
+    hash.finish()
+}
+
+fn supports_binaries() -> bool {
+    let formats = get_value(0, |formats| unsafe {
+        gl::GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS, formats);
+    });
+    formats > 0
+}
+
+fn driver_string(name: GLenum) -> String {
+    unsafe {
+        let ptr = gl::GetString(name);
+        if ptr.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(ptr as *const _).to_string_lossy().into_owned()
+        }
+    }
+}
+
+/// Marks the files written by `write_binary`.
+const MAGIC: &[u8; 4] = b"SGLB";
+
+/// The file holds `MAGIC` and the binary format as a little endian `u32`, followed by the binary.
+fn read_binary(path: &Path) -> Result<(GLenum, Vec<u8>)> {
+    let mut data = std::fs::read(path)?;
+    if data.len() < 8 {
+        bail!("Program binary {} is truncated", path.display());
+    }
+    if &data[..4] != MAGIC {
+        bail!("Program binary {} has an unknown header", path.display());
+    }
+    let binary = data.split_off(8);
+    let format = GLenum::from_le_bytes([data[4], data[5], data[6], data[7]]);
+    Ok((format, binary))
+}
+
+fn write_binary(path: &Path, format: GLenum, binary: &[u8]) -> Result<()> {
+    let mut data = Vec::with_capacity(binary.len() + 8);
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&format.to_le_bytes());
+    data.extend_from_slice(binary);
+
+    // Write to a temporary file first, so an interrupted write is never read as a binary
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, data)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// 64 bit FNV-1a, used since the hash has to stay stable between builds.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fnv(bytes: &[u8]) -> u64 {
+        let mut hash = Fnv::new();
+        hash.write(bytes);
+        hash.finish()
+    }
+
+    #[test]
+    fn fnv_is_stable() {
+        assert_eq!(fnv(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn key_covers_stages_and_settings() {
+        let driver = vec!["vendor".to_owned()];
+        let vertex = [(gl::VERTEX_SHADER, "void main() {}")];
+        let base = key(&vertex, &[], &driver);
+
+        assert_eq!(key(&vertex, &[], &driver), base);
+        assert_ne!(key(&[(gl::FRAGMENT_SHADER, "void main() {}")], &[], &driver), base);
+        assert_ne!(key(&vertex, b"position\0", &driver), base);
+        assert_ne!(key(&vertex, &[], &["other".to_owned()]), base);
+    }
+
+    #[test]
+    fn binaries_round_trip() {
+        let dir = std::env::temp_dir().join(format!("simple_gl_binary_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("program.bin");
+
+        write_binary(&path, 0x8e21, &[1, 2, 3]).unwrap();
+        let read = read_binary(&path);
+
+        std::fs::write(&path, b"SGL").unwrap();
+        let truncated = read_binary(&path);
+        std::fs::write(&path, b"ABCD\x21\x8e\0\0\x01").unwrap();
+        let corrupt = read_binary(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(read.unwrap(), (0x8e21, vec![1, 2, 3]));
+        assert!(truncated.is_err());
+        assert!(corrupt.is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use gl::types::*;
+use super::*;
+use anyhow::Result;
+use std::any::Any;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Caches compiled shader variants keyed by stage, source and defines.
+pub struct ShaderCache {
+    shaders: HashMap<(GLenum, String, Defines), Rc<dyn Any>>,
+}
+
+impl ShaderCache {
+    pub fn new() -> ShaderCache {
+        ShaderCache {
+            shaders: HashMap::new(),
+        }
+    }
+
+    /// Returns the shader compiled from the source and defines, compiling it on first request.
+    pub fn get<T>(&mut self, source: &str, defines: &Defines) -> Result<Rc<Shader<T>>>
+    where
+        T: ShaderExt + 'static
+    {
+        let key = (T::ty(), source.to_owned(), defines.clone());
+        if let Some(shader) = self.shaders.get(&key) {
+            if let Ok(shader) = shader.clone().downcast::<Shader<T>>() {
+                return Ok(shader);
+            }
+        }
+
+        let shader = Rc::new(Shader::<T>::from_source_with_defines(source, defines)?);
+        self.shaders.insert(key, shader.clone());
+        Ok(shader)
+    }
+
+    pub fn len(&self) -> usize {
+        self.shaders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shaders.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.shaders.clear();
+    }
+}
+
+impl Default for ShaderCache {
+    fn default() -> ShaderCache {
+        ShaderCache::new()
+    }
+}
+
+/// Caches linked programs keyed by the shaders attached to them.
+///
+/// Combined with a `ShaderCache` every shader variant has a single id,
+/// so the same combination of variants is only linked once.
+pub struct ProgramCache {
+    programs: HashMap<Vec<GLuint>, Program>,
+}
+
+impl ProgramCache {
+    pub fn new() -> ProgramCache {
+        ProgramCache {
+            programs: HashMap::new(),
+        }
+    }
+
+    /// Returns the program linked from the shaders, linking it on first request.
+    pub fn get(&mut self, shaders: Vec<Rc<dyn ProgramAttachment>>) -> Result<&mut Program> {
+        // The cached program keeps its shaders alive, so their ids can not be reused while the entry exists
+        let key: Vec<GLuint> = shaders.iter().map(|shader| shader.id()).collect();
+        if !self.programs.contains_key(&key) {
+            let program = Program::from_shaders(shaders)?;
+            self.programs.insert(key.clone(), program);
+        }
+        Ok(self.programs.get_mut(&key).unwrap())
+    }
+
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.programs.clear();
+    }
+}
+
+impl Default for ProgramCache {
+    fn default() -> ProgramCache {
+        ProgramCache::new()
+    }
+}
\ No newline at end of file
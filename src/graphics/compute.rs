@@ -0,0 +1,126 @@
+use gl::types::*;
+use super::*;
+use anyhow::Result;
+use crate::storage::buffer::{Buffer, BufferAcces, DispatchIndirectBuffer, DispatchIndirectCommand};
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+
+/// Memory access that should be made visible to commands issued after a barrier.
+pub enum Barrier {
+    VertexAttribArray,
+    ElementArray,
+    Uniform,
+    TextureFetch,
+    ShaderImageAccess,
+    Command,
+    PixelBuffer,
+    TextureUpdate,
+    BufferUpdate,
+    Framebuffer,
+    TransformFeedback,
+    AtomicCounter,
+    ShaderStorage,
+    All
+}
+
+impl Barrier {
+    pub fn value(&self) -> GLbitfield {
+        match self {
+            Barrier::VertexAttribArray => gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT,
+            Barrier::ElementArray => gl::ELEMENT_ARRAY_BARRIER_BIT,
+            Barrier::Uniform => gl::UNIFORM_BARRIER_BIT,
+            Barrier::TextureFetch => gl::TEXTURE_FETCH_BARRIER_BIT,
+            Barrier::ShaderImageAccess => gl::SHADER_IMAGE_ACCESS_BARRIER_BIT,
+            Barrier::Command => gl::COMMAND_BARRIER_BIT,
+            Barrier::PixelBuffer => gl::PIXEL_BUFFER_BARRIER_BIT,
+            Barrier::TextureUpdate => gl::TEXTURE_UPDATE_BARRIER_BIT,
+            Barrier::BufferUpdate => gl::BUFFER_UPDATE_BARRIER_BIT,
+            Barrier::Framebuffer => gl::FRAMEBUFFER_BARRIER_BIT,
+            Barrier::TransformFeedback => gl::TRANSFORM_FEEDBACK_BARRIER_BIT,
+            Barrier::AtomicCounter => gl::ATOMIC_COUNTER_BARRIER_BIT,
+            Barrier::ShaderStorage => gl::SHADER_STORAGE_BARRIER_BIT,
+            Barrier::All => gl::ALL_BARRIER_BITS,
+        }
+    }
+}
+
+/// Issues a `glMemoryBarrier` covering all of the given barriers.
+pub fn memory_barrier(barriers: &[Barrier]) {
+    let bits = barriers.iter().fold(0, |acc, barrier| acc | barrier.value());
+    unsafe {
+        gl::MemoryBarrier(bits);
+    }
+}
+
+/// A program made of a single compute shader, used for dispatching work instead of drawing.
+pub struct ComputeProgram {
+    program: Program
+}
+
+impl ComputeProgram {
+    pub fn from_shader(shader: Rc<ComputeShader>) -> Result<ComputeProgram> {
+        let program = Program::from_shaders(vec![shader])?;
+        Ok(ComputeProgram {
+            program
+        })
+    }
+
+    pub fn from_source(source: &str) -> Result<ComputeProgram> {
+        let shader = ComputeShader::from_source(source)?;
+        ComputeProgram::from_shader(Rc::new(shader))
+    }
+
+    /// Returns the local work group size declared by the compute shader.
+    pub fn work_group_size(&self) -> [GLint; 3] {
+        let mut size = [0; 3];
+        unsafe {
+            gl::GetProgramiv(self.program.id(), gl::COMPUTE_WORK_GROUP_SIZE, size.as_mut_ptr());
+        }
+        size
+    }
+
+    /// Uses the program and launches `x * y * z` work groups.
+    pub fn dispatch(&mut self, x: GLuint, y: GLuint, z: GLuint) {
+        self.program.set_used();
+        unsafe {
+            gl::DispatchCompute(x, y, z);
+        }
+    }
+
+    /// Uses the program and launches work groups with the counts of the command at the element `index`.
+    pub fn dispatch_indirect<Acces>(
+        &mut self,
+        commands: &Buffer<DispatchIndirectCommand, DispatchIndirectBuffer, Acces>,
+        index: GLuint
+    )
+    where
+        Acces: BufferAcces
+    {
+        self.program.set_used();
+        commands.bind();
+        unsafe {
+            gl::DispatchComputeIndirect(
+                (index as usize * std::mem::size_of::<DispatchIndirectCommand>()) as GLintptr
+            );
+        }
+    }
+
+    /// Waits for the given memory accesses by previous dispatches to become visible.
+    pub fn barrier(&self, barriers: &[Barrier]) {
+        memory_barrier(barriers);
+    }
+}
+
+impl Deref for ComputeProgram {
+    type Target = Program;
+
+    fn deref(&self) -> &Program {
+        &self.program
+    }
+}
+
+impl DerefMut for ComputeProgram {
+    fn deref_mut(&mut self) -> &mut Program {
+        &mut self.program
+    }
+}
\ No newline at end of file
@@ -0,0 +1,121 @@
+use gl::types::*;
+use super::*;
+use anyhow::{Result, bail};
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::cell::RefCell;
+use super::uniformcache::UniformCache;
+use super::reflection::find_uniform;
+
+/// A uniform location resolved once by `Program::uniform`, which can only be set with values of type `T`.
+///
+/// Like `Program::set_uniform` the handle sets the uniform of its program, which does not have to be in use.
+/// The location is only valid until the program is linked again, after which the handle fails to set the value.
+#[derive(Debug)]
+pub struct UniformHandle<T> {
+    program: GLuint,
+    location: GLint,
+    cache: Rc<RefCell<UniformCache>>,
+    generation: u64,
+    ty: PhantomData<fn(&T)>,
+}
+
+impl<T> UniformHandle<T>
+where
+    T: UniformType
+{
+    pub fn location(&self) -> GLint {
+        self.location
+    }
+
+    /// Fails if the program was linked, loaded from a binary or replaced since the handle was created.
+    pub fn set(&self, value: &T) -> Result<()> {
+        let mut cache = self.cache.borrow_mut();
+        if cache.generation() != self.generation {
+            bail!("Uniform handle is outdated, the program was linked again since it was created");
+        }
+        if cache.changed(self.location, 1, |out| value.write_bytes(out)) {
+            unsafe {
+                value.upload(self.program, self.location);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T> Clone for UniformHandle<T> {
+    fn clone(&self) -> UniformHandle<T> {
+        UniformHandle {
+            program: self.program,
+            location: self.location,
+            cache: Rc::clone(&self.cache),
+            generation: self.generation,
+            ty: PhantomData,
+        }
+    }
+}
+
+impl Program {
+    /// Resolves the location of the uniform and checks that the shader declares it with a type `T` can be set as.
+    pub fn uniform<T>(&self, name: &str) -> Result<UniformHandle<T>>
+    where
+        T: UniformType
+    {
+        let uniforms = self.cached_active_uniforms();
+        let uniform = match find_uniform(&uniforms, name) {
+            Some(uniform) if uniform.location != -1 => uniform,
+            Some(_) => bail!("Uniform {} is part of a uniform block and has no location", name),
+            None => bail!("Program has no active uniform named {}", name),
+        };
+
+        if !T::accepts(uniform.ty) {
+            bail!(
+                "Uniform {} is declared as {} but is set as {}",
+                name,
+                glsl_type_name(uniform.ty),
+                T::GLSL_NAME
+            );
+        }
+
+        // Elements other than the first of an array have their own location
+        let location = match self.get_uniform_loc(name) {
+            Some(location) => location,
+            None => bail!("Program has no active uniform named {}", name),
+        };
+
+        Ok(UniformHandle {
+            program: self.id(),
+            location,
+            cache: Rc::clone(self.uniform_cache()),
+            generation: self.uniform_cache().borrow().generation(),
+            ty: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testgl;
+
+    #[test]
+    fn fails_after_the_program_is_replaced() {
+        testgl::load();
+        let mut program = Program::new();
+        let cache = program.uniform_cache();
+        let handle = UniformHandle::<f32> {
+            program: program.id(),
+            location: 0,
+            cache: Rc::clone(cache),
+            generation: cache.borrow().generation(),
+            ty: PhantomData,
+        };
+
+        handle.set(&1.0).unwrap();
+        assert_eq!(testgl::take_uniforms(), [(program.id(), 0, 1.0)]);
+
+        program.replace(Program::new());
+        assert!(handle.set(&2.0).is_err());
+        assert!(testgl::take_uniforms().is_empty());
+    }
+}
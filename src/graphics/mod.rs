@@ -1,7 +1,28 @@
 mod shader;
 mod program;
+mod compute;
+mod cache;
+mod binary;
+mod pipeline;
+mod reflection;
 mod uniform;
+mod handle;
+mod uniforms;
+mod uniformcache;
+#[cfg(feature = "hot-reload")]
+mod reload;
 
 pub use shader::*;
-pub use program::Program;
-pub use uniform::*;
\ No newline at end of file
+pub use program::{Program, ProgramAttachment, TransformFeedbackMode};
+pub use compute::{ComputeProgram, Barrier, memory_barrier};
+pub use cache::{ShaderCache, ProgramCache};
+pub use binary::ProgramBinaryCache;
+pub use pipeline::{ProgramPipeline, PipelineStage};
+pub use reflection::{ActiveVariable, BlockMember, InterfaceBlock, ProgramReflection, glsl_type_name};
+pub use uniform::*;
+pub use handle::UniformHandle;
+pub use uniforms::{Uniforms, UniformSetter};
+pub use uniformcache::UniformStats;
+pub use simple_gl_derive::Uniforms;
+#[cfg(feature = "hot-reload")]
+pub use reload::ReloadableProgram;
\ No newline at end of file
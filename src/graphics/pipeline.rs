@@ -0,0 +1,131 @@
+use gl::types::*;
+use super::*;
+use anyhow::Result;
+use crate::error::{get_pipeline_log, ShaderError};
+use crate::get_value;
+use std::rc::Rc;
+
+/// A shader stage of a `ProgramPipeline`.
+pub enum PipelineStage {
+    Vertex,
+    TessControl,
+    TessEvaluation,
+    Geometry,
+    Fragment,
+    Compute,
+    All
+}
+
+impl PipelineStage {
+    pub fn value(&self) -> GLbitfield {
+        match self {
+            PipelineStage::Vertex => gl::VERTEX_SHADER_BIT,
+            PipelineStage::TessControl => gl::TESS_CONTROL_SHADER_BIT,
+            PipelineStage::TessEvaluation => gl::TESS_EVALUATION_SHADER_BIT,
+            PipelineStage::Geometry => gl::GEOMETRY_SHADER_BIT,
+            PipelineStage::Fragment => gl::FRAGMENT_SHADER_BIT,
+            PipelineStage::Compute => gl::COMPUTE_SHADER_BIT,
+            PipelineStage::All => gl::ALL_SHADER_BITS,
+        }
+    }
+}
+
+/// Combines the stages of separable programs without linking them together.
+///
+/// The pipeline keeps the programs used by its stages alive, the same way a `Program` keeps its shaders.
+pub struct ProgramPipeline {
+    id: GLuint,
+    programs: Vec<(GLbitfield, Rc<Program>)>,
+}
+
+impl ProgramPipeline {
+    pub fn new() -> ProgramPipeline {
+        let id = get_value(0, |id| unsafe {
+            gl::GenProgramPipelines(1, id);
+        });
+
+        ProgramPipeline {
+            id,
+            programs: Vec::new(),
+        }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    /// Uses the stages of the program for the given stages of the pipeline.
+    /// The program has to be linked with `Program::set_separable`.
+    pub fn use_stages(&mut self, stages: &[PipelineStage], program: Rc<Program>) {
+        let bits = stages.iter().fold(0, |acc, stage| acc | stage.value());
+        unsafe {
+            gl::UseProgramStages(self.id, bits, program.id());
+        }
+
+        self.release_stages(bits);
+        self.programs.push((bits, program));
+    }
+
+    /// Removes the programs used for the given stages.
+    pub fn clear_stages(&mut self, stages: &[PipelineStage]) {
+        let bits = stages.iter().fold(0, |acc, stage| acc | stage.value());
+        unsafe {
+            gl::UseProgramStages(self.id, bits, 0);
+        }
+        self.release_stages(bits);
+    }
+
+    /// Makes `Program::set_uniform` target the program while the pipeline is bound.
+    pub fn set_active_program(&mut self, program: &Program) {
+        unsafe {
+            gl::ActiveShaderProgram(self.id, program.id());
+        }
+    }
+
+    /// Binds the pipeline, unbinding any program in use through `Program::set_used`, which would take precedence.
+    pub fn bind(&self) {
+        unsafe {
+            gl::UseProgram(0);
+            gl::BindProgramPipeline(self.id);
+        }
+    }
+
+    /// Checks whether the stages of the pipeline fit together and can execute in the current GL state.
+    pub fn validate(&self) -> Result<()> {
+        unsafe { gl::ValidateProgramPipeline(self.id); }
+
+        let success = get_value(1, |success| unsafe {
+            gl::GetProgramPipelineiv(self.id, gl::VALIDATE_STATUS, success);
+        });
+
+        match success {
+            1 => Ok(()),
+            _ => {
+                Err(ShaderError::validation(
+                    get_pipeline_log(self.id)
+                ).into())
+            }
+        }
+    }
+
+    fn release_stages(&mut self, bits: GLbitfield) {
+        for (stages, _) in &mut self.programs {
+            *stages &= !bits;
+        }
+        self.programs.retain(|(stages, _)| *stages != 0);
+    }
+}
+
+impl Default for ProgramPipeline {
+    fn default() -> ProgramPipeline {
+        ProgramPipeline::new()
+    }
+}
+
+impl Drop for ProgramPipeline {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgramPipelines(1, &self.id);
+        }
+    }
+}
\ No newline at end of file
@@ -1,10 +1,13 @@
 use gl::types::*;
 use super::*;
-use anyhow::{Result, anyhow};
-use crate::error::get_program_error;
+use anyhow::{Result, bail};
+use crate::error::{get_program_log, ShaderError};
 use std::rc::Rc;
 use crate::get_value;
 use std::collections::HashMap;
+use std::cell::RefCell;
+use std::ffi::CString;
+use super::uniformcache::{UniformCache, UniformStats};
 
 
 pub trait ProgramAttachment {
@@ -20,10 +23,33 @@ impl<T> ProgramAttachment for Shader<T>
     }
 }
 
+/// How captured varyings are written to transform feedback buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransformFeedbackMode {
+    /// All varyings are written to the buffer bound to index 0.
+    Interleaved,
+    /// Each varying is written to the buffer bound to its own index.
+    Separate
+}
+
+impl TransformFeedbackMode {
+    pub fn value(&self) -> GLenum {
+        match self {
+            TransformFeedbackMode::Interleaved => gl::INTERLEAVED_ATTRIBS,
+            TransformFeedbackMode::Separate => gl::SEPARATE_ATTRIBS,
+        }
+    }
+}
+
 pub struct Program {
     id: GLuint,
     shaders: Vec<Rc<dyn ProgramAttachment>>,
-    uniform_locations: HashMap<String, GLint>,
+    uniform_locations: RefCell<HashMap<String, GLint>>,
+    uniform_cache: Rc<RefCell<UniformCache>>,
+    active_uniforms: RefCell<Option<Rc<Vec<ActiveVariable>>>>,
+    attribute_locations: HashMap<String, GLuint>,
+    output_locations: HashMap<String, GLuint>,
+    transform_feedback: Option<(Vec<CString>, TransformFeedbackMode)>,
 }
 
 impl Program {
@@ -31,7 +57,12 @@ impl Program {
         Program {
             id: unsafe{gl::CreateProgram()},
             shaders: Vec::new(),
-            uniform_locations: HashMap::new(),
+            uniform_locations: RefCell::new(HashMap::new()),
+            uniform_cache: Rc::new(RefCell::new(UniformCache::default())),
+            active_uniforms: RefCell::new(None),
+            attribute_locations: HashMap::new(),
+            output_locations: HashMap::new(),
+            transform_feedback: None,
         }
     }
 
@@ -45,6 +76,18 @@ impl Program {
         Ok(program)
     }
 
+    /// Links the shaders into a program that can be combined with other programs in a `ProgramPipeline`.
+    pub fn separable_from_shaders(shaders: Vec<Rc<dyn ProgramAttachment>>) -> Result<Program>
+    {
+        let mut program = Program::new();
+        program.set_separable(true);
+        for shader in shaders {
+            program.attach(shader);
+        }
+        program.link()?;
+        Ok(program)
+    }
+
     pub fn id(&self) -> GLuint {
         self.id
     }
@@ -56,9 +99,176 @@ impl Program {
         self.shaders.push(shader);
     }
 
+    /// Assigns vertex shader inputs to attribute locations, for shaders without `layout(location = N)`.
+    /// Applied on every following `link`, explicit layout qualifiers in the shader take precedence.
+    pub fn bind_attribute_locations<I, S>(&mut self, locations: I)
+    where
+        I: IntoIterator<Item = (S, GLuint)>,
+        S: Into<String>
+    {
+        self.attribute_locations.extend(locations.into_iter().map(|(name, location)| (name.into(), location)));
+    }
+
+    /// Assigns fragment shader outputs to draw buffers, for shaders without `layout(location = N)`.
+    /// Applied on every following `link`, explicit layout qualifiers in the shader take precedence.
+    pub fn bind_output_locations<I, S>(&mut self, locations: I)
+    where
+        I: IntoIterator<Item = (S, GLuint)>,
+        S: Into<String>
+    {
+        self.output_locations.extend(locations.into_iter().map(|(name, location)| (name.into(), location)));
+    }
+
+    /// Uses the attribute and output locations and transform feedback varyings of the other program on the next `link`.
+    #[cfg(feature = "hot-reload")]
+    pub(crate) fn inherit_link_settings(&mut self, other: &Program) {
+        self.attribute_locations.clone_from(&other.attribute_locations);
+        self.output_locations.clone_from(&other.output_locations);
+        self.transform_feedback.clone_from(&other.transform_feedback);
+    }
+
+    /// Writes the settings applied on `link` in a stable order, so programs set up alike write the same bytes.
+    pub(crate) fn write_link_settings(&self, out: &mut Vec<u8>) {
+        for locations in &[&self.attribute_locations, &self.output_locations] {
+            let mut locations: Vec<_> = locations.iter().collect();
+            locations.sort();
+            for (name, location) in locations {
+                out.extend_from_slice(name.as_bytes());
+                out.push(0);
+                out.extend_from_slice(&location.to_le_bytes());
+            }
+            out.push(0);
+        }
+        if let Some((varyings, mode)) = &self.transform_feedback {
+            out.extend_from_slice(&mode.value().to_le_bytes());
+            for varying in varyings {
+                out.extend_from_slice(varying.as_bytes_with_nul());
+            }
+        }
+    }
+
+    fn apply_locations(&self) -> Result<()> {
+        for (name, location) in &self.attribute_locations {
+            let c_name = CString::new(name.as_str())?;
+            unsafe {
+                gl::BindAttribLocation(self.id, *location, c_name.as_ptr());
+            }
+        }
+        for (name, location) in &self.output_locations {
+            let c_name = CString::new(name.as_str())?;
+            unsafe {
+                gl::BindFragDataLocation(self.id, *location, c_name.as_ptr());
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_transform_feedback(&self) {
+        if let Some((varyings, mode)) = &self.transform_feedback {
+            let pointers: Vec<*const GLchar> = varyings.iter().map(|varying| varying.as_ptr()).collect();
+            unsafe {
+                gl::TransformFeedbackVaryings(
+                    self.id,
+                    pointers.len() as GLsizei,
+                    pointers.as_ptr(),
+                    mode.value()
+                );
+            }
+        }
+    }
+
     pub fn link(&mut self) -> Result<()> {
+        self.apply_locations()?;
+        self.apply_transform_feedback();
         unsafe { gl::LinkProgram(self.id); }
+        self.uniform_locations.get_mut().clear();
+        self.uniform_cache.borrow_mut().clear();
+        self.active_uniforms.get_mut().take();
+        self.check_link_status()
+    }
+
+    /// Selects the outputs of the last vertex processing stage captured by transform feedback.
+    /// Applied on every following `link`, program binaries keep the varyings they were linked with.
+    pub fn set_transform_feedback_varyings(&mut self, varyings: &[&str], mode: TransformFeedbackMode) -> Result<()> {
+        let varyings = varyings
+            .iter()
+            .map(|varying| CString::new(*varying))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        self.transform_feedback = Some((varyings, mode));
+        Ok(())
+    }
+
+    /// Restores a program previously returned by `binary`.
+    /// Fails if the driver rejects the binary, e.g. after a driver update.
+    pub fn from_binary(format: GLenum, binary: &[u8]) -> Result<Program> {
+        let mut program = Program::new();
+        program.load_binary(format, binary)?;
+        Ok(program)
+    }
+
+    pub fn load_binary(&mut self, format: GLenum, binary: &[u8]) -> Result<()> {
+        unsafe {
+            gl::ProgramBinary(
+                self.id,
+                format,
+                binary.as_ptr() as *const GLvoid,
+                binary.len() as GLsizei
+            );
+        }
+        self.uniform_locations.get_mut().clear();
+        self.uniform_cache.borrow_mut().clear();
+        self.active_uniforms.get_mut().take();
+        self.check_link_status()
+    }
+
+    /// Marks the program as usable in a `ProgramPipeline`. Has to be set before linking.
+    pub fn set_separable(&mut self, separable: bool) {
+        unsafe {
+            gl::ProgramParameteri(
+                self.id,
+                gl::PROGRAM_SEPARABLE,
+                separable as GLint
+            );
+        }
+    }
+
+    /// Hints the driver that the binary will be retrieved with `binary`. Has to be set before linking.
+    pub fn set_binary_retrievable(&mut self, retrievable: bool) {
+        unsafe {
+            gl::ProgramParameteri(
+                self.id,
+                gl::PROGRAM_BINARY_RETRIEVABLE_HINT,
+                retrievable as GLint
+            );
+        }
+    }
 
+    /// Returns the binary format and the driver specific binary of the linked program.
+    pub fn binary(&self) -> Result<(GLenum, Vec<u8>)> {
+        let len = get_value(0, |len| unsafe {
+            gl::GetProgramiv(self.id, gl::PROGRAM_BINARY_LENGTH, len);
+        });
+        if len <= 0 {
+            bail!("Program {} has no binary", self.id);
+        }
+
+        let mut binary: Vec<u8> = vec![0; len as usize];
+        let mut written: GLsizei = 0;
+        let mut format: GLenum = 0;
+        unsafe {
+            gl::GetProgramBinary(
+                self.id,
+                len,
+                &mut written,
+                &mut format,
+                binary.as_mut_ptr() as *mut GLvoid
+            );
+        }
+        binary.truncate(written.clamp(0, len) as usize);
+        Ok((format, binary))
+    }
+
+    fn check_link_status(&self) -> Result<()> {
         let success = get_value(1, |success|unsafe {
             gl::GetProgramiv(self.id, gl::LINK_STATUS, success);
         });
@@ -66,36 +276,103 @@ impl Program {
         match success {
             1 => Ok(()),
             _ => {
-                Err(anyhow!(
-                    "Failed to link program: {}", 
-                    get_program_error(self.id)
-                ))
+                Err(ShaderError::link(
+                    get_program_log(self.id)
+                ).into())
+            }
+        }
+    }
+
+    /// Checks whether the program can execute in the current GL state.
+    pub fn validate(&self) -> Result<()> {
+        unsafe { gl::ValidateProgram(self.id); }
+
+        let success = get_value(1, |success|unsafe {
+            gl::GetProgramiv(self.id, gl::VALIDATE_STATUS, success);
+        });
+
+        match success {
+            1 => Ok(()),
+            _ => {
+                Err(ShaderError::validation(
+                    get_program_log(self.id)
+                ).into())
             }
         }
     }
 
-    pub fn set_uniform<K>(&mut self, name: &str, val: K)
+    /// Takes over the GL program and shaders of `other` and deletes the current ones.
+    /// Cached uniform locations belong to the old program, so they are invalidated.
+    pub fn replace(&mut self, mut other: Program) {
+        std::mem::swap(&mut self.id, &mut other.id);
+        std::mem::swap(&mut self.shaders, &mut other.shaders);
+        std::mem::swap(&mut self.attribute_locations, &mut other.attribute_locations);
+        std::mem::swap(&mut self.output_locations, &mut other.output_locations);
+        std::mem::swap(&mut self.transform_feedback, &mut other.transform_feedback);
+        self.uniform_locations.get_mut().clear();
+        self.uniform_cache.borrow_mut().clear();
+        self.active_uniforms.get_mut().take();
+    }
+
+    /// Assigns the uniform block to the binding point, where a `UboBuffer` can be bound with `bind_base`.
+    pub fn bind_uniform_block(&self, name: &str, binding: GLuint) -> Result<()> {
+        let c_name = CString::new(name)?;
+        let index = unsafe { gl::GetUniformBlockIndex(self.id, c_name.as_ptr()) };
+        if index == gl::INVALID_INDEX {
+            bail!("Program has no active uniform block named {}", name);
+        }
+
+        unsafe {
+            gl::UniformBlockBinding(self.id, index, binding);
+        }
+        Ok(())
+    }
+
+    /// Sets the uniform of this program, which does not have to be in use.
+    pub fn set_uniform<K>(&self, name: &str, val: K)
     where
         K: Uniform<K>
     {
         if let Some(loc) = self.get_uniform_loc(name) {
-            unsafe {
-                val.set(loc);
+            if self.uniform_cache.borrow_mut().changed(loc, val.count(), |out| val.write_bytes(out)) {
+                unsafe {
+                    val.set_program(self.id, loc);
+                }
             }
         }
     }
 
+    /// Returns the active uniforms, which are only queried once after each link.
+    pub(crate) fn cached_active_uniforms(&self) -> Rc<Vec<ActiveVariable>> {
+        self.active_uniforms
+            .borrow_mut()
+            .get_or_insert_with(|| Rc::new(self.active_uniforms()))
+            .clone()
+    }
+
+    pub(crate) fn uniform_cache(&self) -> &Rc<RefCell<UniformCache>> {
+        &self.uniform_cache
+    }
+
+    /// Returns how many uniform uploads were done, and how many were skipped because the value was unchanged.
+    pub fn uniform_stats(&self) -> UniformStats {
+        self.uniform_cache.borrow().stats()
+    }
+
+    pub fn reset_uniform_stats(&self) {
+        self.uniform_cache.borrow_mut().reset_stats();
+    }
+
     /// Returns the uniform location for the name or none, if it fails.
-    fn get_uniform_loc(&mut self, name: &str) -> Option<GLint> {
-        if let Some(loc) = self.uniform_locations.get(name) {
+    pub(crate) fn get_uniform_loc(&self, name: &str) -> Option<GLint> {
+        if let Some(loc) = self.uniform_locations.borrow().get(name) {
             return Some(*loc);
         }
 
-        use std::ffi::CString;
         let c_name = CString::new(name).unwrap();
         let loc = unsafe { gl::GetUniformLocation(self.id, c_name.as_ptr()) };
         if loc != -1 {
-            self.uniform_locations.insert(name.to_owned(), loc);
+            self.uniform_locations.borrow_mut().insert(name.to_owned(), loc);
             Some(loc)
         } else {
             None
@@ -108,6 +385,12 @@ impl Program {
 
 }
 
+impl Default for Program {
+    fn default() -> Program {
+        Program::new()
+    }
+}
+
 impl Drop for Program {
     fn drop(&mut self) {
         unsafe {
@@ -117,4 +400,31 @@ impl Drop for Program {
             gl::DeleteProgram(self.id)
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testgl;
+
+    #[test]
+    fn uniforms_are_set_on_their_own_program() {
+        testgl::load();
+        let first = Program::new();
+        let mut second = Program::new();
+        second.set_used();
+
+        first.set_uniform("scale", 1.0f32);
+        second.set_uniform("scale", 2.0f32);
+        first.set_uniform("scale", 1.0f32);
+        second.set_uniform("scale", 1.0f32);
+        first.set_uniform("scale", 3.0f32);
+
+        assert_eq!(testgl::take_uniforms(), [
+            (first.id(), 0, 1.0),
+            (second.id(), 0, 2.0),
+            (second.id(), 0, 1.0),
+            (first.id(), 0, 3.0),
+        ]);
+    }
+}
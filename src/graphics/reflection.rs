@@ -0,0 +1,360 @@
+use gl::types::*;
+use super::*;
+use crate::get_value;
+
+/// An active uniform or vertex attribute of a linked program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveVariable {
+    pub name: String,
+    /// The GL type, e.g. `gl::FLOAT_VEC3`.
+    pub ty: GLenum,
+    /// The number of array elements, 1 for non arrays.
+    pub size: GLint,
+    /// The location, or -1 for uniforms inside a uniform block.
+    pub location: GLint,
+}
+
+/// A member of a uniform or shader storage block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockMember {
+    pub name: String,
+    pub ty: GLenum,
+    /// The number of array elements, 0 for unsized arrays.
+    pub size: GLint,
+    /// The byte offset from the start of the block.
+    pub offset: GLint,
+    /// The bytes between array elements, 0 for non arrays.
+    pub array_stride: GLint,
+    /// The bytes between matrix columns or rows, 0 for non matrices.
+    pub matrix_stride: GLint,
+    pub row_major: bool,
+}
+
+/// A uniform block or shader storage block of a linked program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceBlock {
+    pub name: String,
+    pub index: GLuint,
+    pub binding: GLint,
+    /// The minimum size in bytes of a buffer bound to the block.
+    pub data_size: GLint,
+    pub members: Vec<BlockMember>,
+}
+
+/// Everything a linked program expects from the application.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramReflection {
+    pub uniforms: Vec<ActiveVariable>,
+    pub attributes: Vec<ActiveVariable>,
+    pub uniform_blocks: Vec<InterfaceBlock>,
+    pub storage_blocks: Vec<InterfaceBlock>,
+}
+
+impl ProgramReflection {
+    pub fn uniform(&self, name: &str) -> Option<&ActiveVariable> {
+        self.uniforms.iter().find(|uniform| uniform.name == name)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&ActiveVariable> {
+        self.attributes.iter().find(|attribute| attribute.name == name)
+    }
+
+    pub fn uniform_block(&self, name: &str) -> Option<&InterfaceBlock> {
+        self.uniform_blocks.iter().find(|block| block.name == name)
+    }
+
+    pub fn storage_block(&self, name: &str) -> Option<&InterfaceBlock> {
+        self.storage_blocks.iter().find(|block| block.name == name)
+    }
+}
+
+impl Program {
+    /// Queries all active uniforms, attributes and blocks of the linked program.
+    pub fn reflect(&self) -> ProgramReflection {
+        ProgramReflection {
+            uniforms: self.active_uniforms(),
+            attributes: self.active_attributes(),
+            uniform_blocks: self.interface_blocks(gl::UNIFORM_BLOCK, gl::UNIFORM),
+            storage_blocks: self.interface_blocks(gl::SHADER_STORAGE_BLOCK, gl::BUFFER_VARIABLE),
+        }
+    }
+
+    pub fn active_uniforms(&self) -> Vec<ActiveVariable> {
+        self.active_variables(
+            gl::ACTIVE_UNIFORMS,
+            gl::ACTIVE_UNIFORM_MAX_LENGTH,
+            |program, index, len, written, size, ty, name| unsafe {
+                gl::GetActiveUniform(program, index, len, written, size, ty, name);
+                gl::GetUniformLocation(program, name)
+            }
+        )
+    }
+
+    pub fn active_attributes(&self) -> Vec<ActiveVariable> {
+        self.active_variables(
+            gl::ACTIVE_ATTRIBUTES,
+            gl::ACTIVE_ATTRIBUTE_MAX_LENGTH,
+            |program, index, len, written, size, ty, name| unsafe {
+                gl::GetActiveAttrib(program, index, len, written, size, ty, name);
+                gl::GetAttribLocation(program, name)
+            }
+        )
+    }
+
+    pub fn uniform_blocks(&self) -> Vec<InterfaceBlock> {
+        self.interface_blocks(gl::UNIFORM_BLOCK, gl::UNIFORM)
+    }
+
+    pub fn storage_blocks(&self) -> Vec<InterfaceBlock> {
+        self.interface_blocks(gl::SHADER_STORAGE_BLOCK, gl::BUFFER_VARIABLE)
+    }
+
+    fn active_variables<F>(&self, count_name: GLenum, max_len_name: GLenum, query: F) -> Vec<ActiveVariable>
+    where
+        F: Fn(GLuint, GLuint, GLsizei, &mut GLsizei, &mut GLint, &mut GLenum, *mut GLchar) -> GLint
+    {
+        let count = get_value(0, |count| unsafe {
+            gl::GetProgramiv(self.id(), count_name, count);
+        });
+        let max_len = get_value(0, |len| unsafe {
+            gl::GetProgramiv(self.id(), max_len_name, len);
+        });
+
+        (0..count.max(0) as GLuint)
+            .map(|index| {
+                let mut name: Vec<u8> = vec![0; max_len.max(1) as usize];
+                let mut written: GLsizei = 0;
+                let mut size: GLint = 0;
+                let mut ty: GLenum = 0;
+                let location = query(
+                    self.id(),
+                    index,
+                    name.len() as GLsizei,
+                    &mut written,
+                    &mut size,
+                    &mut ty,
+                    name.as_mut_ptr() as *mut GLchar
+                );
+                name.truncate(written.max(0) as usize);
+
+                ActiveVariable {
+                    name: String::from_utf8_lossy(&name).into_owned(),
+                    ty,
+                    size,
+                    location,
+                }
+            })
+            .collect()
+    }
+
+    fn interface_blocks(&self, block_interface: GLenum, member_interface: GLenum) -> Vec<InterfaceBlock> {
+        let count = get_value(0, |count| unsafe {
+            gl::GetProgramInterfaceiv(self.id(), block_interface, gl::ACTIVE_RESOURCES, count);
+        });
+
+        (0..count.max(0) as GLuint)
+            .map(|index| {
+                let [binding, data_size, member_count] = self.resource_properties(
+                    block_interface,
+                    index,
+                    [gl::BUFFER_BINDING, gl::BUFFER_DATA_SIZE, gl::NUM_ACTIVE_VARIABLES]
+                );
+
+                let mut member_indices: Vec<GLint> = vec![0; member_count.max(0) as usize];
+                if !member_indices.is_empty() {
+                    let props = [gl::ACTIVE_VARIABLES];
+                    unsafe {
+                        gl::GetProgramResourceiv(
+                            self.id(),
+                            block_interface,
+                            index,
+                            1,
+                            props.as_ptr(),
+                            member_indices.len() as GLsizei,
+                            std::ptr::null_mut(),
+                            member_indices.as_mut_ptr()
+                        );
+                    }
+                }
+
+                let mut members: Vec<BlockMember> = member_indices
+                    .iter()
+                    .map(|member| {
+                        let member = *member as GLuint;
+                        let [ty, size, offset, array_stride, matrix_stride, row_major] = self.resource_properties(
+                            member_interface,
+                            member,
+                            [gl::TYPE, gl::ARRAY_SIZE, gl::OFFSET, gl::ARRAY_STRIDE, gl::MATRIX_STRIDE, gl::IS_ROW_MAJOR]
+                        );
+
+                        BlockMember {
+                            name: self.resource_name(member_interface, member),
+                            ty: ty as GLenum,
+                            size,
+                            offset,
+                            array_stride,
+                            matrix_stride,
+                            row_major: row_major != 0,
+                        }
+                    })
+                    .collect();
+                members.sort_by_key(|member| member.offset);
+
+                InterfaceBlock {
+                    name: self.resource_name(block_interface, index),
+                    index,
+                    binding,
+                    data_size,
+                    members,
+                }
+            })
+            .collect()
+    }
+
+    fn resource_properties<const N: usize>(&self, interface: GLenum, index: GLuint, props: [GLenum; N]) -> [GLint; N] {
+        let mut values = [0; N];
+        unsafe {
+            gl::GetProgramResourceiv(
+                self.id(),
+                interface,
+                index,
+                N as GLsizei,
+                props.as_ptr(),
+                N as GLsizei,
+                std::ptr::null_mut(),
+                values.as_mut_ptr()
+            );
+        }
+        values
+    }
+
+    fn resource_name(&self, interface: GLenum, index: GLuint) -> String {
+        let [len] = self.resource_properties(interface, index, [gl::NAME_LENGTH]);
+
+        let mut name: Vec<u8> = vec![0; len.max(1) as usize];
+        let mut written: GLsizei = 0;
+        unsafe {
+            gl::GetProgramResourceName(
+                self.id(),
+                interface,
+                index,
+                name.len() as GLsizei,
+                &mut written,
+                name.as_mut_ptr() as *mut GLchar
+            );
+        }
+        name.truncate(written.max(0) as usize);
+        String::from_utf8_lossy(&name).into_owned()
+    }
+}
+
+/// Finds the active uniform set through `name`, which may be an element of an array.
+///
+/// GL reports an array of basic types as a single uniform named after its first element, `name[0]`,
+/// with the number of elements as its size.
+pub(crate) fn find_uniform<'a>(uniforms: &'a [ActiveVariable], name: &str) -> Option<&'a ActiveVariable> {
+    if let Some(uniform) = uniforms.iter().find(|uniform| uniform.name == name) {
+        return Some(uniform);
+    }
+
+    let (base, index) = split_index(name).unwrap_or((name, 0));
+    let first = format!("{}[0]", base);
+    uniforms
+        .iter()
+        .find(|uniform| uniform.name == first && index < uniform.size.max(0) as usize)
+}
+
+/// Splits `name[i]` into the name and the index.
+fn split_index(name: &str) -> Option<(&str, usize)> {
+    let open = name.strip_suffix(']')?.rfind('[')?;
+    let index = name[open + 1..name.len() - 1].parse().ok()?;
+    Some((&name[..open], index))
+}
+
+/// Returns the GLSL name of a GL type, e.g. `vec3` for `gl::FLOAT_VEC3`.
+pub fn glsl_type_name(ty: GLenum) -> &'static str {
+    match ty {
+        gl::FLOAT => "float",
+        gl::FLOAT_VEC2 => "vec2",
+        gl::FLOAT_VEC3 => "vec3",
+        gl::FLOAT_VEC4 => "vec4",
+        gl::DOUBLE => "double",
+        gl::DOUBLE_VEC2 => "dvec2",
+        gl::DOUBLE_VEC3 => "dvec3",
+        gl::DOUBLE_VEC4 => "dvec4",
+        gl::INT => "int",
+        gl::INT_VEC2 => "ivec2",
+        gl::INT_VEC3 => "ivec3",
+        gl::INT_VEC4 => "ivec4",
+        gl::UNSIGNED_INT => "uint",
+        gl::UNSIGNED_INT_VEC2 => "uvec2",
+        gl::UNSIGNED_INT_VEC3 => "uvec3",
+        gl::UNSIGNED_INT_VEC4 => "uvec4",
+        gl::BOOL => "bool",
+        gl::BOOL_VEC2 => "bvec2",
+        gl::BOOL_VEC3 => "bvec3",
+        gl::BOOL_VEC4 => "bvec4",
+        gl::FLOAT_MAT2 => "mat2",
+        gl::FLOAT_MAT3 => "mat3",
+        gl::FLOAT_MAT4 => "mat4",
+        gl::FLOAT_MAT2x3 => "mat2x3",
+        gl::FLOAT_MAT2x4 => "mat2x4",
+        gl::FLOAT_MAT3x2 => "mat3x2",
+        gl::FLOAT_MAT3x4 => "mat3x4",
+        gl::FLOAT_MAT4x2 => "mat4x2",
+        gl::FLOAT_MAT4x3 => "mat4x3",
+        gl::DOUBLE_MAT2 => "dmat2",
+        gl::DOUBLE_MAT3 => "dmat3",
+        gl::DOUBLE_MAT4 => "dmat4",
+        gl::DOUBLE_MAT2x3 => "dmat2x3",
+        gl::DOUBLE_MAT2x4 => "dmat2x4",
+        gl::DOUBLE_MAT3x2 => "dmat3x2",
+        gl::DOUBLE_MAT3x4 => "dmat3x4",
+        gl::DOUBLE_MAT4x2 => "dmat4x2",
+        gl::DOUBLE_MAT4x3 => "dmat4x3",
+        gl::SAMPLER_1D => "sampler1D",
+        gl::SAMPLER_2D => "sampler2D",
+        gl::SAMPLER_3D => "sampler3D",
+        gl::SAMPLER_CUBE => "samplerCube",
+        gl::SAMPLER_2D_SHADOW => "sampler2DShadow",
+        gl::SAMPLER_2D_ARRAY => "sampler2DArray",
+        gl::SAMPLER_BUFFER => "samplerBuffer",
+        gl::INT_SAMPLER_2D => "isampler2D",
+        gl::UNSIGNED_INT_SAMPLER_2D => "usampler2D",
+        gl::IMAGE_2D => "image2D",
+        gl::UNSIGNED_INT_ATOMIC_COUNTER => "atomic_uint",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uniform(name: &str, size: GLint) -> ActiveVariable {
+        ActiveVariable {
+            name: name.to_owned(),
+            ty: gl::FLOAT,
+            size,
+            location: 0,
+        }
+    }
+
+    #[test]
+    fn finds_array_elements() {
+        let uniforms = vec![
+            uniform("time", 1),
+            uniform("weights[0]", 4),
+            uniform("lights[1].color", 1),
+        ];
+
+        assert_eq!(find_uniform(&uniforms, "time").unwrap().name, "time");
+        assert_eq!(find_uniform(&uniforms, "weights").unwrap().name, "weights[0]");
+        assert_eq!(find_uniform(&uniforms, "weights[0]").unwrap().name, "weights[0]");
+        assert_eq!(find_uniform(&uniforms, "weights[3]").unwrap().name, "weights[0]");
+        assert!(find_uniform(&uniforms, "weights[4]").is_none());
+        assert_eq!(find_uniform(&uniforms, "lights[1].color").unwrap().name, "lights[1].color");
+        assert!(find_uniform(&uniforms, "lights[0].color").is_none());
+        assert!(find_uniform(&uniforms, "time[1]").is_none());
+    }
+}
@@ -0,0 +1,137 @@
+use gl::types::*;
+use super::*;
+use crate::get_value;
+use anyhow::{Result, anyhow};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Receiver};
+
+type StageLoader = Box<dyn Fn(&Path) -> Result<Rc<dyn ProgramAttachment>>>;
+
+/// A program built from shader files that is rebuilt when any of the files change on disk.
+///
+/// The program is only replaced when every stage compiles and the new program links,
+/// otherwise the previous program is kept and `reload` returns the error.
+pub struct ReloadableProgram {
+    program: Program,
+    stages: Vec<(PathBuf, StageLoader)>,
+    watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+}
+
+impl ReloadableProgram {
+    pub fn new() -> Result<ReloadableProgram> {
+        let (sender, events) = channel();
+        let watcher = notify::recommended_watcher(sender)?;
+
+        Ok(ReloadableProgram {
+            program: Program::new(),
+            stages: Vec::new(),
+            watcher,
+            events,
+        })
+    }
+
+    /// Adds a shader stage read from `path` and starts watching the file.
+    /// The stage is compiled by the next call to `build`.
+    pub fn add_stage<T, P>(&mut self, path: P) -> Result<()>
+    where
+        T: ShaderExt + 'static,
+        P: AsRef<Path>
+    {
+        let path = path.as_ref().canonicalize()
+            .map_err(|e| anyhow!("Failed to find {} shader {}: {}", T::name(), path.as_ref().display(), e))?;
+
+        // Editors often replace the file when saving, so the directory is watched instead of the file
+        let dir = path.parent().unwrap_or(&path);
+        self.watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+        let loader: StageLoader = Box::new(|path| {
+            let shader: Rc<dyn ProgramAttachment> = Rc::new(Shader::<T>::from_file(path)?);
+            Ok(shader)
+        });
+        self.stages.push((path, loader));
+        Ok(())
+    }
+
+    /// Compiles all stages and links them, replacing the current program on success.
+    pub fn build(&mut self) -> Result<()> {
+        let mut program = Program::new();
+        program.inherit_link_settings(&self.program);
+        for (path, loader) in &self.stages {
+            program.attach(loader(path)?);
+        }
+        program.link()?;
+
+        let in_use = self.is_in_use();
+        self.program.replace(program);
+        if in_use {
+            self.program.set_used();
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the program if any of the shader files changed since the last call.
+    ///
+    /// Returns `Ok(true)` if the program was replaced. Uniforms have to be set again
+    /// after a replacement since the new program starts with default values.
+    ///
+    /// Errors reported by the file watcher do not stop the remaining events from being handled,
+    /// the first one is returned if no shader file changed.
+    pub fn reload(&mut self) -> Result<bool> {
+        let mut changed = false;
+        let mut watch_error = None;
+        while let Ok(event) = self.events.try_recv() {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    watch_error.get_or_insert(e);
+                    continue;
+                }
+            };
+            if event.kind.is_access() {
+                continue;
+            }
+            changed |= event.paths.iter().any(|path| self.is_stage(path));
+        }
+
+        if changed {
+            self.build()?;
+        } else if let Some(e) = watch_error {
+            return Err(e.into());
+        }
+        Ok(changed)
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    fn is_stage(&self, path: &Path) -> bool {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_owned());
+        self.stages.iter().any(|(stage, _)| *stage == path)
+    }
+
+    fn is_in_use(&self) -> bool {
+        let current = get_value(0, |current| unsafe {
+            gl::GetIntegerv(gl::CURRENT_PROGRAM, current);
+        });
+        current as GLuint == self.program.id()
+    }
+}
+
+impl Deref for ReloadableProgram {
+    type Target = Program;
+
+    fn deref(&self) -> &Program {
+        &self.program
+    }
+}
+
+impl DerefMut for ReloadableProgram {
+    fn deref_mut(&mut self) -> &mut Program {
+        &mut self.program
+    }
+}
\ No newline at end of file
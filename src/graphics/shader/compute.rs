@@ -0,0 +1,30 @@
+use gl::types::*;
+use super::{ShaderExt, Shader};
+use anyhow::Result;
+
+pub type ComputeShader = Shader<Compute>;
+
+pub struct Compute();
+
+impl ShaderExt for Compute {
+    fn new() -> Compute {
+        Compute{}
+    }
+
+    fn ty() -> GLenum {
+        gl::COMPUTE_SHADER
+    }
+
+    fn name() -> &'static str {
+        "compute"
+    }
+}
+
+impl ComputeShader {
+    pub fn from_source(source: &str) -> Result<ComputeShader> {
+        let mut shader = ComputeShader::new();
+        shader.set_source(source);
+        shader.compile()?;
+        Ok(shader)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,187 @@
+use super::{ShaderExt, Shader};
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+/// A set of preprocessor defines used to compile a variant of a shader.
+///
+/// Defines are kept sorted by name, so two sets with the same content compare and hash equal
+/// regardless of the order they were added in.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Defines {
+    values: BTreeMap<String, String>,
+}
+
+impl Defines {
+    pub fn new() -> Defines {
+        Defines {
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a define without a value, e.g. `#define SKINNING`.
+    pub fn define<N>(mut self, name: N) -> Defines
+        where
+            N: Into<String>
+    {
+        self.values.insert(name.into(), String::new());
+        self
+    }
+
+    /// Adds a define with a value, e.g. `#define LIGHT_COUNT 4`.
+    pub fn define_value<N, V>(mut self, name: N, value: V) -> Defines
+        where
+            N: Into<String>,
+            V: ToString
+    {
+        self.values.insert(name.into(), value.to_string());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Returns the source with a `#define` line for each define inserted after the `#version` directive,
+    /// or at the start if there is none. A `#line` directive keeps the line numbers of the original source.
+    pub fn inject(&self, source: &str) -> String {
+        if self.is_empty() {
+            return source.to_owned();
+        }
+
+        let (version, body, first_line) = match find_version(source) {
+            Some((end, line)) => (&source[..end], &source[end..], line + 1),
+            None => ("", source, 1),
+        };
+
+        let mut output = String::with_capacity(source.len());
+        output.push_str(version);
+        if !version.is_empty() && !version.ends_with('\n') {
+            output.push('\n');
+        }
+        for (name, value) in self.iter() {
+            if value.is_empty() {
+                output.push_str(&format!("#define {}\n", name));
+            } else {
+                output.push_str(&format!("#define {} {}\n", name, value));
+            }
+        }
+        output.push_str(&format!("#line {}\n", first_line));
+        output.push_str(body);
+        output
+    }
+}
+
+/// Returns the byte offset after the `#version` line and its line number,
+/// if the directive is the first thing in the source after comments and blank lines.
+fn find_version(source: &str) -> Option<(usize, usize)> {
+    let mut in_comment = false;
+    let mut offset = 0;
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        let end = offset + line.len();
+
+        // Strip the comments from the start of the line
+        let mut rest = line.trim();
+        loop {
+            if in_comment {
+                match rest.find("*/") {
+                    Some(close) => {
+                        rest = rest[close + 2..].trim_start();
+                        in_comment = false;
+                    },
+                    None => {
+                        rest = "";
+                        break;
+                    },
+                }
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                rest = comment;
+                in_comment = true;
+            } else if rest.starts_with("//") {
+                rest = "";
+                break;
+            } else {
+                break;
+            }
+        }
+
+        if !rest.is_empty() {
+            let is_version = rest
+                .strip_prefix('#')
+                .is_some_and(|directive| directive.trim_start().starts_with("version"));
+            return if is_version { Some((end, index + 1)) } else { None };
+        }
+        offset = end;
+    }
+    None
+}
+
+impl<T> Shader<T>
+where
+    T: ShaderExt
+{
+    /// Compiles the source with the given defines inserted after its `#version` directive.
+    pub fn from_source_with_defines(source: &str, defines: &Defines) -> Result<Shader<T>> {
+        let mut shader = Shader::new();
+        shader.set_source(defines.inject(source));
+        shader.compile()?;
+        Ok(shader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn injects_after_version() {
+        let defines = Defines::new().define("SKINNING").define_value("LIGHTS", 4);
+        let source = "#version 330 core\nvoid main() {}\n";
+
+        assert_eq!(defines.inject(source), "\
+#version 330 core
+#define LIGHTS 4
+#define SKINNING
+#line 2
+void main() {}
+");
+    }
+
+    #[test]
+    fn injects_after_version_below_comments() {
+        let defines = Defines::new().define("SKINNING");
+        let source = "// Copyright\n/* License\n   text */\n\n#version 450\nvoid main() {}";
+
+        assert_eq!(defines.inject(source), "\
+// Copyright
+/* License
+   text */
+
+#version 450
+#define SKINNING
+#line 6
+void main() {}");
+    }
+
+    #[test]
+    fn injects_at_start_without_version() {
+        let defines = Defines::new().define("SKINNING");
+        let source = "// No version\nvoid main() {}\n#version 330\n";
+
+        assert_eq!(defines.inject(source), "\
+#define SKINNING
+#line 1
+// No version
+void main() {}
+#version 330
+");
+    }
+
+    #[test]
+    fn empty_defines_keep_source() {
+        assert_eq!(Defines::new().inject("#version 330\n"), "#version 330\n");
+    }
+}
@@ -1,9 +1,23 @@
+mod compute;
+mod defines;
 mod fragment;
 mod geometry;
+mod preprocessor;
 mod shader;
+mod spirv;
+mod tesscontrol;
+mod tessevaluation;
+#[cfg(feature = "validation")]
+mod validate;
 mod vertex;
 
-pub use vertex::VertexShader;
-pub use fragment::FragmentShader;
-pub use geometry::GeometryShader;
-pub use shader::{Shader, ShaderExt};
\ No newline at end of file
+pub use vertex::{VertexShader, Vertex};
+pub use fragment::{FragmentShader, Fragment};
+pub use geometry::{GeometryShader, Geometry};
+pub use compute::{ComputeShader, Compute};
+pub use tesscontrol::{TessControlShader, TessControl};
+pub use tessevaluation::{TessEvaluationShader, TessEvaluation};
+pub use shader::{Shader, ShaderExt};
+pub use preprocessor::{Preprocessor, ProcessedSource};
+pub use defines::Defines;
+pub use spirv::{SpecializationConstants, SHADER_BINARY_FORMAT_SPIR_V, load_spirv_with, spirv_supported};
\ No newline at end of file
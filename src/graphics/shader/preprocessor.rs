@@ -0,0 +1,282 @@
+use anyhow::{Result, anyhow, bail};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The output of running the `Preprocessor` on a shader source.
+pub struct ProcessedSource {
+    /// The shader source with all includes expanded.
+    pub source: String,
+    /// The name of every file that went into the source, indexed by the
+    /// source string number used in the emitted `#line` directives.
+    pub files: Vec<String>,
+}
+
+/// Resolves `#include "file.glsl"` directives in shader sources.
+///
+/// Includes are looked up in the in-memory files first, then relative to the
+/// including file and finally in each search path in the order they were added.
+/// Every file is given a source string number and `#line` directives are emitted
+/// around each include, so driver errors can be mapped back through `ProcessedSource::files`.
+pub struct Preprocessor {
+    search_paths: Vec<PathBuf>,
+    files: HashMap<String, String>,
+}
+
+impl Preprocessor {
+    pub fn new() -> Preprocessor {
+        Preprocessor {
+            search_paths: Vec::new(),
+            files: HashMap::new(),
+        }
+    }
+
+    pub fn add_search_path<P>(&mut self, path: P)
+        where
+            P: AsRef<Path>
+    {
+        self.search_paths.push(path.as_ref().to_owned());
+    }
+
+    /// Registers an in-memory file that can be included by `name`.
+    pub fn add_file<N, S>(&mut self, name: N, source: S)
+        where
+            N: Into<String>,
+            S: Into<String>
+    {
+        self.files.insert(name.into(), source.into());
+    }
+
+    /// Expands all includes in `source`, which is reported as `name` in errors and `#line` directives.
+    pub fn process(&self, name: &str, source: &str) -> Result<ProcessedSource> {
+        let mut state = State {
+            output: String::new(),
+            files: vec![name.to_owned()],
+            stack: Vec::new(),
+        };
+        self.process_file(&mut state, name, name, None, source)?;
+
+        Ok(ProcessedSource {
+            source: state.output,
+            files: state.files,
+        })
+    }
+
+    /// Reads `path` from disk and expands all includes in it.
+    pub fn process_file_path<P>(&self, path: P) -> Result<ProcessedSource>
+        where
+            P: AsRef<Path>
+    {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read shader {}: {}", path.display(), e))?;
+        let name = path.to_string_lossy().into_owned();
+        let key = std::fs::canonicalize(path)
+            .map_err(|e| anyhow!("Failed to resolve shader {}: {}", path.display(), e))?;
+
+        let mut state = State {
+            output: String::new(),
+            files: vec![name.clone()],
+            stack: Vec::new(),
+        };
+        self.process_file(&mut state, &name, &key.to_string_lossy(), path.parent(), &source)?;
+
+        Ok(ProcessedSource {
+            source: state.output,
+            files: state.files,
+        })
+    }
+
+    /// Expands the includes of the file `name`, where `key` identifies the file in cycle detection.
+    fn process_file(&self, state: &mut State, name: &str, key: &str, dir: Option<&Path>, source: &str) -> Result<()> {
+        if state.stack.iter().any(|(included, _)| included == key) {
+            let names: Vec<&str> = state.stack.iter().map(|(_, name)| name.as_str()).collect();
+            bail!("Include cycle detected: {} -> {}", names.join(" -> "), name);
+        }
+        state.stack.push((key.to_owned(), name.to_owned()));
+
+        let index = state.index_of(name);
+        for (line_index, line) in source.lines().enumerate() {
+            let line_number = line_index + 1;
+            match parse_include(line) {
+                Some(Ok(include)) => {
+                    let (include_name, include_dir, include_source) = self.resolve(include, dir)
+                        .map_err(|e| anyhow!("{}:{}: {}", name, line_number, e))?;
+
+                    let include_index = state.index_of(&include_name);
+                    state.output.push_str(&format!("#line 1 {}\n", include_index));
+                    self.process_file(state, &include_name, &include_name, include_dir.as_deref(), &include_source)?;
+                    state.output.push_str(&format!("#line {} {}\n", line_number + 1, index));
+                },
+                Some(Err(e)) => {
+                    bail!("{}:{}: {}", name, line_number, e);
+                },
+                None => {
+                    state.output.push_str(line);
+                    state.output.push('\n');
+                    // The version directive has to stay first, so the initial line marker goes after it
+                    if state.stack.len() == 1 && is_version(line) {
+                        state.output.push_str(&format!("#line {} {}\n", line_number + 1, index));
+                    }
+                }
+            }
+        }
+
+        state.stack.pop();
+        Ok(())
+    }
+
+    /// Returns the name, directory and source of the included file.
+    /// Files on disk are named by their canonical path, so every path to the same file has the same name.
+    fn resolve(&self, include: &str, dir: Option<&Path>) -> Result<(String, Option<PathBuf>, String)> {
+        if let Some(source) = self.files.get(include) {
+            return Ok((include.to_owned(), None, source.clone()));
+        }
+
+        let candidates = dir
+            .into_iter()
+            .chain(self.search_paths.iter().map(|p| p.as_path()))
+            .map(|dir| dir.join(include));
+
+        for path in candidates {
+            if path.is_file() {
+                let path = std::fs::canonicalize(&path)
+                    .map_err(|e| anyhow!("Failed to resolve include {}: {}", path.display(), e))?;
+                let source = std::fs::read_to_string(&path)
+                    .map_err(|e| anyhow!("Failed to read include {}: {}", path.display(), e))?;
+                let dir = path.parent().map(|p| p.to_owned());
+                return Ok((path.to_string_lossy().into_owned(), dir, source));
+            }
+        }
+
+        Err(anyhow!("Could not find include \"{}\"", include))
+    }
+}
+
+impl Default for Preprocessor {
+    fn default() -> Preprocessor {
+        Preprocessor::new()
+    }
+}
+
+struct State {
+    output: String,
+    files: Vec<String>,
+    /// The key and name of each file being expanded.
+    stack: Vec<(String, String)>,
+}
+
+impl State {
+    fn index_of(&mut self, name: &str) -> usize {
+        match self.files.iter().position(|file| file == name) {
+            Some(index) => index,
+            None => {
+                self.files.push(name.to_owned());
+                self.files.len() - 1
+            }
+        }
+    }
+}
+
+fn is_version(line: &str) -> bool {
+    directive(line).is_some_and(|rest| rest.starts_with("version"))
+}
+
+/// Returns the text after `#` if the line is a preprocessor directive.
+fn directive(line: &str) -> Option<&str> {
+    line.trim_start()
+        .strip_prefix('#')
+        .map(|rest| rest.trim_start())
+}
+
+/// Returns the file name if the line is an include directive.
+fn parse_include(line: &str) -> Option<Result<&str>> {
+    let rest = directive(line)?.strip_prefix("include")?.trim();
+
+    let quoted = rest.strip_prefix('"').and_then(|r| r.strip_suffix('"'));
+    let angled = rest.strip_prefix('<').and_then(|r| r.strip_suffix('>'));
+    let parsed = quoted
+        .or(angled)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| anyhow!("Malformed include directive: {}", line.trim()));
+    Some(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_nested_includes() {
+        let mut preprocessor = Preprocessor::new();
+        preprocessor.add_file("light.glsl", "#include \"math.glsl\"\nvec3 light;");
+        preprocessor.add_file("math.glsl", "float pi;");
+
+        let processed = preprocessor.process("main.frag", "#include \"light.glsl\"\nvoid main() {}").unwrap();
+
+        assert_eq!(processed.files, vec!["main.frag", "light.glsl", "math.glsl"]);
+        assert_eq!(processed.source, "\
+#line 1 1
+#line 1 2
+float pi;
+#line 2 1
+vec3 light;
+#line 2 0
+void main() {}
+");
+    }
+
+    #[test]
+    fn detects_include_cycles() {
+        let mut preprocessor = Preprocessor::new();
+        preprocessor.add_file("a.glsl", "#include \"b.glsl\"");
+        preprocessor.add_file("b.glsl", "#include \"a.glsl\"");
+
+        let error = preprocessor.process("main.vert", "#include \"a.glsl\"").err().unwrap();
+        assert_eq!(error.to_string(), "Include cycle detected: main.vert -> a.glsl -> b.glsl -> a.glsl");
+    }
+
+    #[test]
+    fn marks_lines_after_version() {
+        let mut preprocessor = Preprocessor::new();
+        preprocessor.add_file("common.glsl", "uniform float time;");
+
+        let source = "#version 330 core\n#include \"common.glsl\"\nvoid main() {}";
+        let processed = preprocessor.process("main.vert", source).unwrap();
+        assert_eq!(processed.source, "\
+#version 330 core
+#line 2 0
+#line 1 1
+uniform float time;
+#line 3 0
+void main() {}
+");
+    }
+
+    #[test]
+    fn marks_lines_after_late_version() {
+        let source = "// License header\n\n#version 450\nvoid main() {}";
+        let processed = Preprocessor::new().process("main.vert", source).unwrap();
+        assert_eq!(processed.source, "\
+// License header
+
+#version 450
+#line 4 0
+void main() {}
+");
+    }
+
+    #[test]
+    fn detects_self_includes_through_relative_paths() {
+        let dir = std::env::temp_dir().join(format!("simple_gl_preprocessor_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        std::fs::write(dir.join("a.glsl"), "#include \"./a.glsl\"").unwrap();
+        std::fs::write(dir.join("lib/b.glsl"), "#include \"../lib/b.glsl\"").unwrap();
+
+        let self_include = Preprocessor::new().process_file_path(dir.join("a.glsl"));
+        let parent_include = Preprocessor::new().process_file_path(dir.join("lib/b.glsl"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(self_include.err().unwrap().to_string().starts_with("Include cycle detected"));
+        assert!(parent_include.err().unwrap().to_string().starts_with("Include cycle detected"));
+    }
+}
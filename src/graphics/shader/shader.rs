@@ -1,8 +1,10 @@
 pub use gl::types::*;
-pub use anyhow::{Result, anyhow};
-pub use crate::error::get_shader_error;
+pub use anyhow::Result;
+use anyhow::anyhow;
+use crate::error::{get_shader_log, ShaderError};
 use crate::get_value;
 use std::ffi::CString;
+use std::path::Path;
 
 pub trait ShaderExt {
     fn new() -> Self;
@@ -15,6 +17,7 @@ pub struct Shader<T>
         T: ShaderExt
 {
     id: GLuint,
+    #[allow(dead_code)]
     ty: T
 }
 
@@ -29,6 +32,21 @@ where
         }
     }
 
+    /// Reads the source from `path` and compiles it.
+    pub fn from_file<P>(path: P) -> Result<Shader<T>>
+        where
+            P: AsRef<Path>
+    {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {} shader {}: {}", T::name(), path.display(), e))?;
+
+        let mut shader = Shader::new();
+        shader.set_source(source);
+        shader.compile()?;
+        Ok(shader)
+    }
+
     pub fn id(&self) -> GLuint {
         self.id
     }
@@ -73,7 +91,10 @@ where
         unsafe {
             gl::CompileShader(self.id);
         }
+        self.check_compile_status()
+    }
 
+    pub(crate) fn check_compile_status(&self) -> Result<()> {
         let success: GLint = get_value(1, |success|unsafe {
             gl::GetShaderiv(self.id, gl::COMPILE_STATUS, success);
         });
@@ -81,16 +102,24 @@ where
         match success {
             1 => Ok(()),
             _ => {
-                Err(anyhow!(
-                    "Failed to compile {} shader: {}", 
-                    T::name(), 
-                    get_shader_error(self.id)
-                ))
+                Err(ShaderError::compile(
+                    T::name(),
+                    get_shader_log(self.id)
+                ).into())
             }
         }
     }
 }
 
+impl<T> Default for Shader<T>
+where
+    T: ShaderExt
+{
+    fn default() -> Shader<T> {
+        Shader::new()
+    }
+}
+
 impl<T> Drop for Shader<T>
 where
     T: ShaderExt
@@ -0,0 +1,157 @@
+use gl::types::*;
+use super::{ShaderExt, Shader};
+use anyhow::{Result, anyhow, bail};
+use crate::get_value;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_void;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+/// `GL_SHADER_BINARY_FORMAT_SPIR_V`, missing from the generated bindings which stop at GL 4.5.
+pub const SHADER_BINARY_FORMAT_SPIR_V: GLenum = 0x9551;
+
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+type SpecializeShaderFn = unsafe extern "system" fn(GLuint, *const GLchar, GLuint, *const GLuint, *const GLuint);
+
+static SPECIALIZE_SHADER: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
+
+/// Loads `glSpecializeShader`, which is not part of the `gl` bindings.
+/// Call it with the same loader passed to `gl::load_with`.
+pub fn load_spirv_with<F>(mut loadfn: F)
+where
+    F: FnMut(&'static str) -> *const c_void
+{
+    let mut ptr = loadfn("glSpecializeShader");
+    if ptr.is_null() {
+        ptr = loadfn("glSpecializeShaderARB");
+    }
+    SPECIALIZE_SHADER.store(ptr as *mut c_void, Ordering::SeqCst);
+}
+
+/// Returns true if the context supports SPIR-V shaders and `load_spirv_with` has been called.
+pub fn spirv_supported() -> bool {
+    if SPECIALIZE_SHADER.load(Ordering::SeqCst).is_null() {
+        return false;
+    }
+
+    let major = get_value(0, |major| unsafe {
+        gl::GetIntegerv(gl::MAJOR_VERSION, major);
+    });
+    let minor = get_value(0, |minor| unsafe {
+        gl::GetIntegerv(gl::MINOR_VERSION, minor);
+    });
+    (major, minor) >= (4, 6) || has_extension("GL_ARB_gl_spirv")
+}
+
+fn has_extension(name: &str) -> bool {
+    let count = get_value(0, |count| unsafe {
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, count);
+    });
+
+    (0..count.max(0) as GLuint).any(|index| unsafe {
+        let ptr = gl::GetStringi(gl::EXTENSIONS, index);
+        !ptr.is_null() && CStr::from_ptr(ptr as *const _).to_bytes() == name.as_bytes()
+    })
+}
+
+/// Values for the specialization constants of a SPIR-V shader, keyed by their `constant_id`.
+#[derive(Clone, Debug, Default)]
+pub struct SpecializationConstants {
+    ids: Vec<GLuint>,
+    values: Vec<GLuint>,
+}
+
+impl SpecializationConstants {
+    pub fn new() -> SpecializationConstants {
+        SpecializationConstants {
+            ids: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Sets the constant to the raw 32 bit value.
+    pub fn set(mut self, id: GLuint, value: u32) -> SpecializationConstants {
+        match self.ids.iter().position(|i| *i == id) {
+            Some(index) => self.values[index] = value,
+            None => {
+                self.ids.push(id);
+                self.values.push(value);
+            }
+        }
+        self
+    }
+
+    pub fn set_i32(self, id: GLuint, value: i32) -> SpecializationConstants {
+        self.set(id, value as u32)
+    }
+
+    pub fn set_f32(self, id: GLuint, value: f32) -> SpecializationConstants {
+        self.set(id, value.to_bits())
+    }
+
+    pub fn set_bool(self, id: GLuint, value: bool) -> SpecializationConstants {
+        self.set(id, value as u32)
+    }
+}
+
+impl<T> Shader<T>
+where
+    T: ShaderExt
+{
+    /// Creates a shader from a SPIR-V module, specialized for the entry point and constants.
+    pub fn from_spirv(binary: &[u8], entry_point: &str, constants: &SpecializationConstants) -> Result<Shader<T>> {
+        let mut shader = Shader::new();
+        shader.set_spirv(binary)?;
+        shader.specialize(entry_point, constants)?;
+        Ok(shader)
+    }
+
+    /// Loads a SPIR-V module as the shader binary, replacing any source. It has to be specialized before linking.
+    pub fn set_spirv(&mut self, binary: &[u8]) -> Result<()> {
+        if !spirv_supported() {
+            bail!("SPIR-V shaders require GL 4.6 or GL_ARB_gl_spirv and a call to load_spirv_with");
+        }
+        if binary.len() < 4 || binary.len() % 4 != 0 {
+            bail!("Invalid SPIR-V module of {} bytes for {} shader", binary.len(), T::name());
+        }
+        let magic = u32::from_le_bytes([binary[0], binary[1], binary[2], binary[3]]);
+        if magic != SPIRV_MAGIC {
+            bail!("Invalid SPIR-V magic number {:#x} for {} shader", magic, T::name());
+        }
+
+        let id = self.id();
+        unsafe {
+            gl::ShaderBinary(
+                1,
+                &id,
+                SHADER_BINARY_FORMAT_SPIR_V,
+                binary.as_ptr() as *const GLvoid,
+                binary.len() as GLsizei
+            );
+        }
+        Ok(())
+    }
+
+    /// Selects the entry point and sets the specialization constants, taking the place of `compile`.
+    pub fn specialize(&mut self, entry_point: &str, constants: &SpecializationConstants) -> Result<()> {
+        let ptr = SPECIALIZE_SHADER.load(Ordering::SeqCst);
+        if ptr.is_null() {
+            bail!("glSpecializeShader is not loaded, call load_spirv_with after creating the context");
+        }
+        let specialize: SpecializeShaderFn = unsafe { std::mem::transmute(ptr) };
+
+        let entry_point = CString::new(entry_point)
+            .map_err(|e| anyhow!("Invalid entry point for {} shader: {}", T::name(), e))?;
+        unsafe {
+            specialize(
+                self.id(),
+                entry_point.as_ptr(),
+                constants.ids.len() as GLuint,
+                constants.ids.as_ptr(),
+                constants.values.as_ptr()
+            );
+        }
+
+        self.check_compile_status()
+    }
+}
\ No newline at end of file
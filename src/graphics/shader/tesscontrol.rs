@@ -0,0 +1,30 @@
+use gl::types::*;
+use super::{ShaderExt, Shader};
+use anyhow::Result;
+
+pub type TessControlShader = Shader<TessControl>;
+
+pub struct TessControl();
+
+impl ShaderExt for TessControl {
+    fn new() -> TessControl {
+        TessControl{}
+    }
+
+    fn ty() -> GLenum {
+        gl::TESS_CONTROL_SHADER
+    }
+
+    fn name() -> &'static str {
+        "tessellation control"
+    }
+}
+
+impl TessControlShader {
+    pub fn from_source(source: &str) -> Result<TessControlShader> {
+        let mut shader = TessControlShader::new();
+        shader.set_source(source);
+        shader.compile()?;
+        Ok(shader)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,30 @@
+use gl::types::*;
+use super::{ShaderExt, Shader};
+use anyhow::Result;
+
+pub type TessEvaluationShader = Shader<TessEvaluation>;
+
+pub struct TessEvaluation();
+
+impl ShaderExt for TessEvaluation {
+    fn new() -> TessEvaluation {
+        TessEvaluation{}
+    }
+
+    fn ty() -> GLenum {
+        gl::TESS_EVALUATION_SHADER
+    }
+
+    fn name() -> &'static str {
+        "tessellation evaluation"
+    }
+}
+
+impl TessEvaluationShader {
+    pub fn from_source(source: &str) -> Result<TessEvaluationShader> {
+        let mut shader = TessEvaluationShader::new();
+        shader.set_source(source);
+        shader.compile()?;
+        Ok(shader)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,90 @@
+use gl::types::*;
+use super::{ShaderExt, Shader};
+use crate::error::{Diagnostic, ShaderError, Severity, parse_log};
+use anyhow::{Result, anyhow};
+use glslang::{
+    Compiler, CompilerOptions, ShaderInput, ShaderMessage, ShaderSource, ShaderStage,
+    SourceLanguage, Target,
+};
+
+fn stage(ty: GLenum) -> Option<ShaderStage> {
+    match ty {
+        gl::VERTEX_SHADER => Some(ShaderStage::Vertex),
+        gl::TESS_CONTROL_SHADER => Some(ShaderStage::TesselationControl),
+        gl::TESS_EVALUATION_SHADER => Some(ShaderStage::TesselationEvaluation),
+        gl::GEOMETRY_SHADER => Some(ShaderStage::Geometry),
+        gl::FRAGMENT_SHADER => Some(ShaderStage::Fragment),
+        gl::COMPUTE_SHADER => Some(ShaderStage::Compute),
+        _ => None,
+    }
+}
+
+impl<T> Shader<T>
+where
+    T: ShaderExt
+{
+    /// Parses and validates the source for the stage of `T` without a GL context.
+    ///
+    /// Returns the warnings on success. On failure the error is a `ShaderError::Compile`
+    /// with the same diagnostics a driver compile would report.
+    pub fn validate_source(source: &str) -> Result<Vec<Diagnostic>> {
+        let stage = stage(T::ty())
+            .ok_or_else(|| anyhow!("Validation of {} shaders is not supported", T::name()))?;
+        let compiler = Compiler::acquire()
+            .ok_or_else(|| anyhow!("Failed to acquire the GLSL validator"))?;
+
+        let source = ShaderSource::from(source.trim());
+        let options = CompilerOptions {
+            source_language: SourceLanguage::GLSL,
+            target: Target::None(None),
+            version_profile: None,
+            messages: ShaderMessage::DEFAULT,
+        };
+        let input = ShaderInput::new(&source, stage, &options, None::<&[(&str, Option<&str>)]>, None)
+            .map_err(|e| anyhow!("Failed to validate {} shader: {}", T::name(), e))?;
+
+        let shader = glslang::Shader::new(compiler, input).parse();
+        match shader {
+            Ok(shader) => {
+                let diagnostics = parse_log(&shader.get_log())
+                    .into_iter()
+                    .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+                    .collect();
+                Ok(diagnostics)
+            },
+            Err(glslang::error::GlslangError::ParseError(log))
+            | Err(glslang::error::GlslangError::PreprocessError(log)) => {
+                Err(ShaderError::compile(T::name(), log.log).into())
+            },
+            Err(e) => Err(anyhow!("Failed to validate {} shader: {}", T::name(), e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use crate::error::ShaderError;
+
+    #[test]
+    fn validates_drawing_example() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/example/drawing/resources/shaders/cube/");
+        let read = |name: &str| std::fs::read_to_string(format!("{}{}", dir, name)).unwrap();
+
+        VertexShader::validate_source(&read("cube.vert")).unwrap();
+        GeometryShader::validate_source(&read("cube.geom")).unwrap();
+        FragmentShader::validate_source(&read("cube.frag")).unwrap();
+    }
+
+    #[test]
+    fn reports_diagnostics() {
+        let source = "#version 330 core\nvoid main() {\n    gl_Position = vec5(0.0);\n}\n";
+        let error = VertexShader::validate_source(source).unwrap_err();
+        let error = error.downcast_ref::<ShaderError>().unwrap();
+
+        assert_eq!(error.stage(), Some("vertex"));
+        let diagnostic = &error.diagnostics()[0];
+        assert_eq!(diagnostic.source, Some(0));
+        assert_eq!(diagnostic.line, Some(3));
+    }
+}
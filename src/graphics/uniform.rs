@@ -1,57 +1,268 @@
+use nalgebra as na;
 use gl::types::*;
+use super::{Uniforms, UniformSetter};
 
 pub trait Uniform<T> {
+    /// # Safety
+    /// Requires a current GL context with the owning program in use.
     unsafe fn set(self, loc: GLint);
+
+    /// Sets the uniform of `program`, whether or not it is in use.
+    ///
+    /// Defaults to making the program current for `set`, and restoring the previous program afterwards.
+    ///
+    /// # Safety
+    /// Requires a current GL context.
+    unsafe fn set_program(self, program: GLuint, loc: GLint)
+    where
+        Self: Sized
+    {
+        let current = crate::get_value(0, |current| gl::GetIntegerv(gl::CURRENT_PROGRAM, current)) as GLuint;
+        if current == program {
+            self.set(loc);
+        } else {
+            gl::UseProgram(program);
+            self.set(loc);
+            gl::UseProgram(current);
+        }
+    }
+
+    /// Writes the uploaded value, used to skip uploads of an unchanged value.
+    ///
+    /// Values that write nothing are uploaded every time they are set.
+    fn write_bytes(&self, _out: &mut Vec<u8>) {}
+
+    /// The number of consecutive locations set, the length of arrays.
+    fn count(&self) -> GLsizei {
+        1
+    }
+}
+
+/// Returns the memory of the value.
+/// Only used for the plain numeric types uniforms are made of, which have no padding.
+pub(crate) fn raw_bytes<T: ?Sized>(value: &T) -> &[u8] {
+    unsafe {
+        std::slice::from_raw_parts(value as *const T as *const u8, std::mem::size_of_val(value))
+    }
+}
+
+/// Uploads a matrix given as rows, `[[T; COLUMNS]; ROWS]`, by letting GL transpose it.
+///
+/// Matrices given as plain arrays are read as columns, `[[T; ROWS]; COLUMNS]`, like GLSL and nalgebra.
+pub struct RowMajor<T>(pub T);
+
+/// Uploads a matrix given as columns, the same as passing the array itself.
+pub struct ColumnMajor<T>(pub T);
+
+impl<K> Uniform<ColumnMajor<K>> for ColumnMajor<K>
+where
+    K: Uniform<K>
+{
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        self.0.write_bytes(out);
+    }
+
+    fn count(&self) -> GLsizei {
+        self.0.count()
+    }
+
+    unsafe fn set(self, loc: GLint) {
+        self.0.set(loc);
+    }
+
+    unsafe fn set_program(self, program: GLuint, loc: GLint) {
+        self.0.set_program(program, loc);
+    }
 }
 
 macro_rules! Uniform {
-    ($ty:ty, $name0:ident, $name1:ident, $name2:ident, $name3:ident, $name4:ident, $name5:ident, $name6:ident, $name7:ident) => {
+    ($ty:ty, $name0:ident, $name1:ident, $name2:ident, $name3:ident, $name4:ident, $name5:ident, $name6:ident, $name7:ident,
+     $prog0:ident, $prog1:ident, $prog2:ident, $prog3:ident, $prog4:ident, $prog5:ident, $prog6:ident, $prog7:ident) => {
         impl<'a> Uniform<$ty> for $ty {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(self));
+            }
+
             unsafe fn set(self, loc: GLint) {
                 gl::$name0(loc, self);
             }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog0(program, loc, self);
+            }
         }
         impl<'a> Uniform<&'a [$ty; 2]> for &'a [$ty; 2] {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
             unsafe fn set(self, loc: GLint) {
                 gl::$name1(loc, self[0], self[1]);
             }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog1(program, loc, self[0], self[1]);
+            }
         }
         impl<'a> Uniform<&'a [$ty; 3]> for &'a [$ty; 3] {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
             unsafe fn set(self, loc: GLint) {
                 gl::$name2(loc, self[0], self[1], self[2]);
             }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog2(program, loc, self[0], self[1], self[2]);
+            }
         }
         impl<'a> Uniform<&'a [$ty; 4]> for &'a [$ty; 4] {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
             unsafe fn set(self, loc: GLint) {
                 gl::$name3(loc, self[0], self[1], self[2], self[3]);
             }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog3(program, loc, self[0], self[1], self[2], self[3]);
+            }
         }
 
         impl<'a> Uniform<&'a [$ty]> for &'a [$ty] {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            fn count(&self) -> GLsizei {
+                self.len() as GLsizei
+            }
+
             unsafe fn set(self, loc: GLint) {
                 gl::$name4(loc, self.len() as GLsizei, self.as_ptr() as *const $ty)
             }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog4(program, loc, self.len() as GLsizei, self.as_ptr() as *const $ty)
+            }
         }
         impl<'a> Uniform<&'a [[$ty; 2]]> for &'a [[$ty; 2]] {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            fn count(&self) -> GLsizei {
+                self.len() as GLsizei
+            }
+
             unsafe fn set(self, loc: GLint) {
                 gl::$name5(loc, self.len() as GLsizei, self.as_ptr() as *const $ty)
             }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog5(program, loc, self.len() as GLsizei, self.as_ptr() as *const $ty)
+            }
         }
         impl<'a> Uniform<&'a [[$ty; 3]]> for &'a [[$ty; 3]] {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            fn count(&self) -> GLsizei {
+                self.len() as GLsizei
+            }
+
             unsafe fn set(self, loc: GLint) {
                 gl::$name6(loc, self.len() as GLsizei, self.as_ptr() as *const $ty)
             }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog6(program, loc, self.len() as GLsizei, self.as_ptr() as *const $ty)
+            }
         }
         impl<'a> Uniform<&'a [[$ty; 4]]> for &'a [[$ty; 4]] {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            fn count(&self) -> GLsizei {
+                self.len() as GLsizei
+            }
+
             unsafe fn set(self, loc: GLint) {
                 gl::$name7(loc, self.len() as GLsizei, self.as_ptr() as *const $ty)
             }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog7(program, loc, self.len() as GLsizei, self.as_ptr() as *const $ty)
+            }
         }
     };
-    (matrix, $name:ident, $ty0:ty, $inverse:ident) => {
-        impl<'a> Uniform<&'a [$ty0]> for &'a [$ty0] {
+    (matrix, $ty:tt, $cols:tt, $rows:tt, $name:ident, $prog:ident) => {
+        impl<'a> Uniform<&'a [[$ty; $rows]; $cols]> for &'a [[$ty; $rows]; $cols] {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            unsafe fn set(self, loc: GLint) {
+                gl::$name(loc, 1, gl::FALSE, self.as_ptr() as *const $ty);
+            }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog(program, loc, 1, gl::FALSE, self.as_ptr() as *const $ty);
+            }
+        }
+        impl<'a> Uniform<&'a [[[$ty; $rows]; $cols]]> for &'a [[[$ty; $rows]; $cols]] {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            fn count(&self) -> GLsizei {
+                self.len() as GLsizei
+            }
+
+            unsafe fn set(self, loc: GLint) {
+                gl::$name(loc, self.len() as GLsizei, gl::FALSE, self.as_ptr() as *const $ty);
+            }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog(program, loc, self.len() as GLsizei, gl::FALSE, self.as_ptr() as *const $ty);
+            }
+        }
+        impl<'a> Uniform<RowMajor<&'a [[$ty; $cols]; $rows]>> for RowMajor<&'a [[$ty; $cols]; $rows]> {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(self.0));
+                // The same values uploaded as columns are a different matrix
+                out.push(1);
+            }
+
             unsafe fn set(self, loc: GLint) {
-                gl::$name(loc, self.len() as GLsizei, $inverse as GLboolean, self.as_ptr() as *const GLfloat);
+                gl::$name(loc, 1, gl::TRUE, self.0.as_ptr() as *const $ty);
+            }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog(program, loc, 1, gl::TRUE, self.0.as_ptr() as *const $ty);
+            }
+        }
+        impl<'a> Uniform<RowMajor<&'a [[[$ty; $cols]; $rows]]>> for RowMajor<&'a [[[$ty; $cols]; $rows]]> {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(self.0));
+                // The same values uploaded as columns are a different matrix
+                out.push(1);
+            }
+
+            fn count(&self) -> GLsizei {
+                self.0.len() as GLsizei
+            }
+
+            unsafe fn set(self, loc: GLint) {
+                gl::$name(loc, self.0.len() as GLsizei, gl::TRUE, self.0.as_ptr() as *const $ty);
+            }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog(program, loc, self.0.len() as GLsizei, gl::TRUE, self.0.as_ptr() as *const $ty);
             }
         }
     };
@@ -62,16 +273,423 @@ macro_rules! Uniform {
 }
 
 Uniform! {
-    i32, Uniform1i, Uniform2i, Uniform3i, Uniform4i, Uniform1iv, Uniform2iv, Uniform3iv, Uniform4iv:
-    u32, Uniform1ui, Uniform2ui, Uniform3ui, Uniform4ui, Uniform1uiv, Uniform2uiv, Uniform3uiv, Uniform4uiv:
-    f32, Uniform1f, Uniform2f, Uniform3f, Uniform4f, Uniform1fv, Uniform2fv, Uniform3fv, Uniform4fv:
-    matrix, UniformMatrix2fv, [[GLfloat; 2]; 2], false:
-    matrix, UniformMatrix3fv, [[GLfloat; 3]; 3], false:
-    matrix, UniformMatrix4fv, [[GLfloat; 4]; 4], false:
-    matrix, UniformMatrix2x3fv, [[GLfloat; 2]; 3], true:
-    matrix, UniformMatrix2x3fv, [[GLfloat; 3]; 2], false:
-    matrix, UniformMatrix2x4fv, [[GLfloat; 2]; 4], true:
-    matrix, UniformMatrix2x4fv, [[GLfloat; 4]; 2], false:
-    matrix, UniformMatrix3x4fv, [[GLfloat; 3]; 4], true:
-    matrix, UniformMatrix3x4fv, [[GLfloat; 4]; 3], false
-}
\ No newline at end of file
+    i32, Uniform1i, Uniform2i, Uniform3i, Uniform4i, Uniform1iv, Uniform2iv, Uniform3iv, Uniform4iv,
+        ProgramUniform1i, ProgramUniform2i, ProgramUniform3i, ProgramUniform4i, ProgramUniform1iv, ProgramUniform2iv, ProgramUniform3iv, ProgramUniform4iv:
+    u32, Uniform1ui, Uniform2ui, Uniform3ui, Uniform4ui, Uniform1uiv, Uniform2uiv, Uniform3uiv, Uniform4uiv,
+        ProgramUniform1ui, ProgramUniform2ui, ProgramUniform3ui, ProgramUniform4ui, ProgramUniform1uiv, ProgramUniform2uiv, ProgramUniform3uiv, ProgramUniform4uiv:
+    f32, Uniform1f, Uniform2f, Uniform3f, Uniform4f, Uniform1fv, Uniform2fv, Uniform3fv, Uniform4fv,
+        ProgramUniform1f, ProgramUniform2f, ProgramUniform3f, ProgramUniform4f, ProgramUniform1fv, ProgramUniform2fv, ProgramUniform3fv, ProgramUniform4fv:
+    f64, Uniform1d, Uniform2d, Uniform3d, Uniform4d, Uniform1dv, Uniform2dv, Uniform3dv, Uniform4dv,
+        ProgramUniform1d, ProgramUniform2d, ProgramUniform3d, ProgramUniform4d, ProgramUniform1dv, ProgramUniform2dv, ProgramUniform3dv, ProgramUniform4dv:
+    matrix, GLfloat, 2, 2, UniformMatrix2fv, ProgramUniformMatrix2fv:
+    matrix, GLfloat, 3, 3, UniformMatrix3fv, ProgramUniformMatrix3fv:
+    matrix, GLfloat, 4, 4, UniformMatrix4fv, ProgramUniformMatrix4fv:
+    matrix, GLfloat, 2, 3, UniformMatrix2x3fv, ProgramUniformMatrix2x3fv:
+    matrix, GLfloat, 3, 2, UniformMatrix3x2fv, ProgramUniformMatrix3x2fv:
+    matrix, GLfloat, 2, 4, UniformMatrix2x4fv, ProgramUniformMatrix2x4fv:
+    matrix, GLfloat, 4, 2, UniformMatrix4x2fv, ProgramUniformMatrix4x2fv:
+    matrix, GLfloat, 3, 4, UniformMatrix3x4fv, ProgramUniformMatrix3x4fv:
+    matrix, GLfloat, 4, 3, UniformMatrix4x3fv, ProgramUniformMatrix4x3fv:
+    matrix, GLdouble, 2, 2, UniformMatrix2dv, ProgramUniformMatrix2dv:
+    matrix, GLdouble, 3, 3, UniformMatrix3dv, ProgramUniformMatrix3dv:
+    matrix, GLdouble, 4, 4, UniformMatrix4dv, ProgramUniformMatrix4dv:
+    matrix, GLdouble, 2, 3, UniformMatrix2x3dv, ProgramUniformMatrix2x3dv:
+    matrix, GLdouble, 3, 2, UniformMatrix3x2dv, ProgramUniformMatrix3x2dv:
+    matrix, GLdouble, 2, 4, UniformMatrix2x4dv, ProgramUniformMatrix2x4dv:
+    matrix, GLdouble, 4, 2, UniformMatrix4x2dv, ProgramUniformMatrix4x2dv:
+    matrix, GLdouble, 3, 4, UniformMatrix3x4dv, ProgramUniformMatrix3x4dv:
+    matrix, GLdouble, 4, 3, UniformMatrix4x3dv, ProgramUniformMatrix4x3dv
+}
+
+impl Uniform<bool> for bool {
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(raw_bytes(self));
+    }
+
+    unsafe fn set(self, loc: GLint) {
+        gl::Uniform1i(loc, self as GLint);
+    }
+
+    unsafe fn set_program(self, program: GLuint, loc: GLint) {
+        gl::ProgramUniform1i(program, loc, self as GLint);
+    }
+}
+
+impl<'a> Uniform<&'a [bool; 2]> for &'a [bool; 2] {
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(raw_bytes(*self));
+    }
+
+    unsafe fn set(self, loc: GLint) {
+        gl::Uniform2i(loc, self[0] as GLint, self[1] as GLint);
+    }
+
+    unsafe fn set_program(self, program: GLuint, loc: GLint) {
+        gl::ProgramUniform2i(program, loc, self[0] as GLint, self[1] as GLint);
+    }
+}
+
+impl<'a> Uniform<&'a [bool; 3]> for &'a [bool; 3] {
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(raw_bytes(*self));
+    }
+
+    unsafe fn set(self, loc: GLint) {
+        gl::Uniform3i(loc, self[0] as GLint, self[1] as GLint, self[2] as GLint);
+    }
+
+    unsafe fn set_program(self, program: GLuint, loc: GLint) {
+        gl::ProgramUniform3i(program, loc, self[0] as GLint, self[1] as GLint, self[2] as GLint);
+    }
+}
+
+impl<'a> Uniform<&'a [bool; 4]> for &'a [bool; 4] {
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(raw_bytes(*self));
+    }
+
+    unsafe fn set(self, loc: GLint) {
+        gl::Uniform4i(loc, self[0] as GLint, self[1] as GLint, self[2] as GLint, self[3] as GLint);
+    }
+
+    unsafe fn set_program(self, program: GLuint, loc: GLint) {
+        gl::ProgramUniform4i(program, loc, self[0] as GLint, self[1] as GLint, self[2] as GLint, self[3] as GLint);
+    }
+}
+
+impl<'a> Uniform<&'a [bool]> for &'a [bool] {
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(raw_bytes(*self));
+    }
+
+    fn count(&self) -> GLsizei {
+        self.len() as GLsizei
+    }
+
+    unsafe fn set(self, loc: GLint) {
+        let values: Vec<GLint> = self.iter().map(|v| *v as GLint).collect();
+        gl::Uniform1iv(loc, values.len() as GLsizei, values.as_ptr());
+    }
+
+    unsafe fn set_program(self, program: GLuint, loc: GLint) {
+        let values: Vec<GLint> = self.iter().map(|v| *v as GLint).collect();
+        gl::ProgramUniform1iv(program, loc, values.len() as GLsizei, values.as_ptr());
+    }
+}
+
+// nalgebra stores matrices column-major, so they are uploaded without transposing
+macro_rules! NalgebraUniform {
+    (vector, $ty:ty, $name:ident, $prog:ident; $($t:tt)*) => {
+        impl<'a> Uniform<&'a $ty> for &'a $ty {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            unsafe fn set(self, loc: GLint) {
+                gl::$name(loc, 1, self.as_ptr());
+            }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog(program, loc, 1, self.as_ptr());
+            }
+        }
+        impl<'a> Uniform<&'a [$ty]> for &'a [$ty] {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            fn count(&self) -> GLsizei {
+                self.len() as GLsizei
+            }
+
+            unsafe fn set(self, loc: GLint) {
+                gl::$name(loc, self.len() as GLsizei, self.as_ptr() as *const _);
+            }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog(program, loc, self.len() as GLsizei, self.as_ptr() as *const _);
+            }
+        }
+        NalgebraUniform!{$($t)*}
+    };
+    (point, $ty:ty, $name:ident, $prog:ident; $($t:tt)*) => {
+        impl<'a> Uniform<&'a $ty> for &'a $ty {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            unsafe fn set(self, loc: GLint) {
+                gl::$name(loc, 1, self.coords.as_ptr());
+            }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog(program, loc, 1, self.coords.as_ptr());
+            }
+        }
+        impl<'a> Uniform<&'a [$ty]> for &'a [$ty] {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            fn count(&self) -> GLsizei {
+                self.len() as GLsizei
+            }
+
+            unsafe fn set(self, loc: GLint) {
+                gl::$name(loc, self.len() as GLsizei, self.as_ptr() as *const _);
+            }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog(program, loc, self.len() as GLsizei, self.as_ptr() as *const _);
+            }
+        }
+        NalgebraUniform!{$($t)*}
+    };
+    (matrix, $ty:ty, $name:ident, $prog:ident; $($t:tt)*) => {
+        impl<'a> Uniform<&'a $ty> for &'a $ty {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            unsafe fn set(self, loc: GLint) {
+                gl::$name(loc, 1, gl::FALSE, self.as_ptr());
+            }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog(program, loc, 1, gl::FALSE, self.as_ptr());
+            }
+        }
+        impl<'a> Uniform<&'a [$ty]> for &'a [$ty] {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            fn count(&self) -> GLsizei {
+                self.len() as GLsizei
+            }
+
+            unsafe fn set(self, loc: GLint) {
+                gl::$name(loc, self.len() as GLsizei, gl::FALSE, self.as_ptr() as *const _);
+            }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                gl::$prog(program, loc, self.len() as GLsizei, gl::FALSE, self.as_ptr() as *const _);
+            }
+        }
+        NalgebraUniform!{$($t)*}
+    };
+    (homogeneous, $ty:ty; $($t:tt)*) => {
+        impl<'a> Uniform<&'a $ty> for &'a $ty {
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(raw_bytes(*self));
+            }
+
+            unsafe fn set(self, loc: GLint) {
+                let matrix: na::Matrix4<f32> = self.to_homogeneous();
+                gl::UniformMatrix4fv(loc, 1, gl::FALSE, matrix.as_ptr());
+            }
+
+            unsafe fn set_program(self, program: GLuint, loc: GLint) {
+                let matrix: na::Matrix4<f32> = self.to_homogeneous();
+                gl::ProgramUniformMatrix4fv(program, loc, 1, gl::FALSE, matrix.as_ptr());
+            }
+        }
+        NalgebraUniform!{$($t)*}
+    };
+    () => {};
+}
+
+NalgebraUniform! {
+    vector, na::Vector2<f32>, Uniform2fv, ProgramUniform2fv;
+    vector, na::Vector3<f32>, Uniform3fv, ProgramUniform3fv;
+    vector, na::Vector4<f32>, Uniform4fv, ProgramUniform4fv;
+    vector, na::Vector2<i32>, Uniform2iv, ProgramUniform2iv;
+    vector, na::Vector3<i32>, Uniform3iv, ProgramUniform3iv;
+    vector, na::Vector4<i32>, Uniform4iv, ProgramUniform4iv;
+    vector, na::Vector2<u32>, Uniform2uiv, ProgramUniform2uiv;
+    vector, na::Vector3<u32>, Uniform3uiv, ProgramUniform3uiv;
+    vector, na::Vector4<u32>, Uniform4uiv, ProgramUniform4uiv;
+    vector, na::Vector2<f64>, Uniform2dv, ProgramUniform2dv;
+    vector, na::Vector3<f64>, Uniform3dv, ProgramUniform3dv;
+    vector, na::Vector4<f64>, Uniform4dv, ProgramUniform4dv;
+    point, na::Point2<f32>, Uniform2fv, ProgramUniform2fv;
+    point, na::Point3<f32>, Uniform3fv, ProgramUniform3fv;
+    matrix, na::Matrix2<f32>, UniformMatrix2fv, ProgramUniformMatrix2fv;
+    matrix, na::Matrix3<f32>, UniformMatrix3fv, ProgramUniformMatrix3fv;
+    matrix, na::Matrix4<f32>, UniformMatrix4fv, ProgramUniformMatrix4fv;
+    matrix, na::Matrix2<f64>, UniformMatrix2dv, ProgramUniformMatrix2dv;
+    matrix, na::Matrix3<f64>, UniformMatrix3dv, ProgramUniformMatrix3dv;
+    matrix, na::Matrix4<f64>, UniformMatrix4dv, ProgramUniformMatrix4dv;
+    homogeneous, na::Isometry3<f32>;
+    homogeneous, na::Similarity3<f32>;
+    homogeneous, na::Perspective3<f32>;
+    homogeneous, na::Orthographic3<f32>;
+    homogeneous, na::UnitQuaternion<f32>;
+}
+
+/// A value with a fixed GLSL type, which can be set through a `UniformHandle`.
+pub trait UniformType {
+    /// The GLSL type the value is uploaded as.
+    const GLSL_NAME: &'static str;
+
+    /// Returns true if a uniform of the GL type can be set from the value.
+    fn accepts(ty: GLenum) -> bool;
+
+    /// Uploads the value to the uniform of `program`, whether or not it is in use.
+    ///
+    /// # Safety
+    /// Requires a current GL context.
+    unsafe fn upload(&self, program: GLuint, loc: GLint);
+
+    /// Writes the uploaded value, used to skip uploads of an unchanged value.
+    fn write_bytes(&self, out: &mut Vec<u8>);
+}
+
+/// Returns true for sampler and image types, which are set with an `i32` texture unit.
+pub fn is_opaque_type(ty: GLenum) -> bool {
+    matches!(
+        ty,
+        gl::SAMPLER_1D | gl::SAMPLER_2D | gl::SAMPLER_3D | gl::SAMPLER_CUBE |
+        gl::SAMPLER_1D_SHADOW | gl::SAMPLER_2D_SHADOW | gl::SAMPLER_CUBE_SHADOW |
+        gl::SAMPLER_1D_ARRAY | gl::SAMPLER_2D_ARRAY | gl::SAMPLER_1D_ARRAY_SHADOW | gl::SAMPLER_2D_ARRAY_SHADOW |
+        gl::SAMPLER_2D_MULTISAMPLE | gl::SAMPLER_2D_MULTISAMPLE_ARRAY | gl::SAMPLER_CUBE_MAP_ARRAY |
+        gl::SAMPLER_BUFFER | gl::SAMPLER_2D_RECT |
+        gl::INT_SAMPLER_1D | gl::INT_SAMPLER_2D | gl::INT_SAMPLER_3D | gl::INT_SAMPLER_CUBE |
+        gl::INT_SAMPLER_2D_ARRAY | gl::INT_SAMPLER_BUFFER |
+        gl::UNSIGNED_INT_SAMPLER_1D | gl::UNSIGNED_INT_SAMPLER_2D | gl::UNSIGNED_INT_SAMPLER_3D |
+        gl::UNSIGNED_INT_SAMPLER_CUBE | gl::UNSIGNED_INT_SAMPLER_2D_ARRAY | gl::UNSIGNED_INT_SAMPLER_BUFFER |
+        gl::IMAGE_1D | gl::IMAGE_2D | gl::IMAGE_3D | gl::IMAGE_CUBE | gl::IMAGE_2D_ARRAY | gl::IMAGE_BUFFER |
+        gl::INT_IMAGE_2D | gl::UNSIGNED_INT_IMAGE_2D
+    )
+}
+
+macro_rules! UniformType {
+    (value, $ty:ty, $glsl:expr, $($gl:path)|+; $($t:tt)*) => {
+        impl UniformType for $ty {
+            const GLSL_NAME: &'static str = $glsl;
+
+            fn accepts(ty: GLenum) -> bool {
+                matches!(ty, $($gl)|+)
+            }
+
+            unsafe fn upload(&self, program: GLuint, loc: GLint) {
+                Uniform::set_program(*self, program, loc);
+            }
+
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                Uniform::<$ty>::write_bytes(self, out);
+            }
+        }
+        impl Uniforms for $ty {
+            fn set_uniforms(&self, setter: &mut UniformSetter, name: &str) {
+                setter.set(name, self);
+            }
+        }
+        UniformType!{$($t)*}
+    };
+    (ref, $ty:ty, $glsl:expr, $($gl:path)|+; $($t:tt)*) => {
+        impl UniformType for $ty {
+            const GLSL_NAME: &'static str = $glsl;
+
+            fn accepts(ty: GLenum) -> bool {
+                matches!(ty, $($gl)|+)
+            }
+
+            unsafe fn upload(&self, program: GLuint, loc: GLint) {
+                Uniform::set_program(self, program, loc);
+            }
+
+            fn write_bytes(&self, out: &mut Vec<u8>) {
+                Uniform::<&$ty>::write_bytes(&self, out);
+            }
+        }
+        impl Uniforms for $ty {
+            fn set_uniforms(&self, setter: &mut UniformSetter, name: &str) {
+                setter.set(name, self);
+            }
+        }
+        UniformType!{$($t)*}
+    };
+    () => {};
+}
+
+impl UniformType for i32 {
+    const GLSL_NAME: &'static str = "int";
+
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::INT || ty == gl::BOOL || is_opaque_type(ty)
+    }
+
+    unsafe fn upload(&self, program: GLuint, loc: GLint) {
+        Uniform::set_program(*self, program, loc);
+    }
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        Uniform::<i32>::write_bytes(self, out);
+    }
+}
+
+impl Uniforms for i32 {
+    fn set_uniforms(&self, setter: &mut UniformSetter, name: &str) {
+        setter.set(name, self);
+    }
+}
+
+UniformType! {
+    value, u32, "uint", gl::UNSIGNED_INT | gl::BOOL;
+    value, f32, "float", gl::FLOAT | gl::BOOL;
+    value, f64, "double", gl::DOUBLE;
+    value, bool, "bool", gl::BOOL;
+    ref, [i32; 2], "ivec2", gl::INT_VEC2 | gl::BOOL_VEC2;
+    ref, [i32; 3], "ivec3", gl::INT_VEC3 | gl::BOOL_VEC3;
+    ref, [i32; 4], "ivec4", gl::INT_VEC4 | gl::BOOL_VEC4;
+    ref, [u32; 2], "uvec2", gl::UNSIGNED_INT_VEC2 | gl::BOOL_VEC2;
+    ref, [u32; 3], "uvec3", gl::UNSIGNED_INT_VEC3 | gl::BOOL_VEC3;
+    ref, [u32; 4], "uvec4", gl::UNSIGNED_INT_VEC4 | gl::BOOL_VEC4;
+    ref, [f32; 2], "vec2", gl::FLOAT_VEC2 | gl::BOOL_VEC2;
+    ref, [f32; 3], "vec3", gl::FLOAT_VEC3 | gl::BOOL_VEC3;
+    ref, [f32; 4], "vec4", gl::FLOAT_VEC4 | gl::BOOL_VEC4;
+    ref, [f64; 2], "dvec2", gl::DOUBLE_VEC2;
+    ref, [f64; 3], "dvec3", gl::DOUBLE_VEC3;
+    ref, [f64; 4], "dvec4", gl::DOUBLE_VEC4;
+    ref, [bool; 2], "bvec2", gl::BOOL_VEC2;
+    ref, [bool; 3], "bvec3", gl::BOOL_VEC3;
+    ref, [bool; 4], "bvec4", gl::BOOL_VEC4;
+    ref, [[f32; 2]; 2], "mat2", gl::FLOAT_MAT2;
+    ref, [[f32; 3]; 3], "mat3", gl::FLOAT_MAT3;
+    ref, [[f32; 4]; 4], "mat4", gl::FLOAT_MAT4;
+    ref, [[f32; 3]; 2], "mat2x3", gl::FLOAT_MAT2x3;
+    ref, [[f32; 2]; 3], "mat3x2", gl::FLOAT_MAT3x2;
+    ref, [[f32; 4]; 2], "mat2x4", gl::FLOAT_MAT2x4;
+    ref, [[f32; 2]; 4], "mat4x2", gl::FLOAT_MAT4x2;
+    ref, [[f32; 4]; 3], "mat3x4", gl::FLOAT_MAT3x4;
+    ref, [[f32; 3]; 4], "mat4x3", gl::FLOAT_MAT4x3;
+    ref, [[f64; 2]; 2], "dmat2", gl::DOUBLE_MAT2;
+    ref, [[f64; 3]; 3], "dmat3", gl::DOUBLE_MAT3;
+    ref, [[f64; 4]; 4], "dmat4", gl::DOUBLE_MAT4;
+    ref, na::Vector2<f32>, "vec2", gl::FLOAT_VEC2;
+    ref, na::Vector3<f32>, "vec3", gl::FLOAT_VEC3;
+    ref, na::Vector4<f32>, "vec4", gl::FLOAT_VEC4;
+    ref, na::Vector2<i32>, "ivec2", gl::INT_VEC2;
+    ref, na::Vector3<i32>, "ivec3", gl::INT_VEC3;
+    ref, na::Vector4<i32>, "ivec4", gl::INT_VEC4;
+    ref, na::Vector2<u32>, "uvec2", gl::UNSIGNED_INT_VEC2;
+    ref, na::Vector3<u32>, "uvec3", gl::UNSIGNED_INT_VEC3;
+    ref, na::Vector4<u32>, "uvec4", gl::UNSIGNED_INT_VEC4;
+    ref, na::Vector2<f64>, "dvec2", gl::DOUBLE_VEC2;
+    ref, na::Vector3<f64>, "dvec3", gl::DOUBLE_VEC3;
+    ref, na::Vector4<f64>, "dvec4", gl::DOUBLE_VEC4;
+    ref, na::Point2<f32>, "vec2", gl::FLOAT_VEC2;
+    ref, na::Point3<f32>, "vec3", gl::FLOAT_VEC3;
+    ref, na::Matrix2<f32>, "mat2", gl::FLOAT_MAT2;
+    ref, na::Matrix3<f32>, "mat3", gl::FLOAT_MAT3;
+    ref, na::Matrix4<f32>, "mat4", gl::FLOAT_MAT4;
+    ref, na::Matrix2<f64>, "dmat2", gl::DOUBLE_MAT2;
+    ref, na::Matrix3<f64>, "dmat3", gl::DOUBLE_MAT3;
+    ref, na::Matrix4<f64>, "dmat4", gl::DOUBLE_MAT4;
+    ref, na::Isometry3<f32>, "mat4", gl::FLOAT_MAT4;
+    ref, na::Similarity3<f32>, "mat4", gl::FLOAT_MAT4;
+    ref, na::Perspective3<f32>, "mat4", gl::FLOAT_MAT4;
+    ref, na::Orthographic3<f32>, "mat4", gl::FLOAT_MAT4;
+    ref, na::UnitQuaternion<f32>, "mat4", gl::FLOAT_MAT4;
+}
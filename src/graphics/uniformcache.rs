@@ -0,0 +1,143 @@
+use gl::types::*;
+use std::collections::HashMap;
+
+/// The number of uniform uploads done and skipped by a program, see `Program::uniform_stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UniformStats {
+    pub uploaded: u64,
+    pub skipped: u64,
+}
+
+/// A shadow copy of the last value uploaded to each uniform location of a program.
+///
+/// Arrays are kept as one value at their first location, which is forgotten when any of its
+/// locations is set on its own.
+///
+/// Values set with `gl::Uniform*` directly are not seen by the cache, and will not be overwritten
+/// if the same value is then set through the program again.
+#[derive(Debug, Default)]
+pub(crate) struct UniformCache {
+    /// The number of locations and the bytes of each value, keyed by its first location.
+    values: HashMap<GLint, (GLsizei, Vec<u8>)>,
+    scratch: Vec<u8>,
+    stats: UniformStats,
+    generation: u64,
+}
+
+impl UniformCache {
+    /// Returns true if the value written by `write` differs from the last value uploaded to the `count`
+    /// locations starting at `loc`.
+    ///
+    /// A value that writes nothing is always uploaded, and forgets every value as it may set any number of locations.
+    pub fn changed<F>(&mut self, loc: GLint, count: GLsizei, write: F) -> bool
+    where
+        F: FnOnce(&mut Vec<u8>)
+    {
+        self.scratch.clear();
+        write(&mut self.scratch);
+
+        if self.scratch.is_empty() {
+            self.values.clear();
+            self.stats.uploaded += 1;
+            return true;
+        }
+
+        if let Some((cached_count, value)) = self.values.get(&loc) {
+            if *cached_count == count && *value == self.scratch {
+                self.stats.skipped += 1;
+                return false;
+            }
+        }
+
+        // Values sharing a location with the new one are no longer what the program holds
+        let end = loc + count;
+        self.values.retain(|&start, (len, _)| start + *len <= loc || end <= start);
+        self.values.insert(loc, (count, self.scratch.clone()));
+        self.stats.uploaded += 1;
+        true
+    }
+
+    /// Forgets all values, linking resets the uniforms of a program.
+    /// Starts a new generation, as the uniform locations may have changed as well.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.generation += 1;
+    }
+
+    /// Counts the links of the program, so a `UniformHandle` can tell that its location is outdated.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn stats(&self) -> UniformStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = UniformStats::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(cache: &mut UniformCache, loc: GLint, value: f32) -> bool {
+        cache.changed(loc, 1, |out| out.extend_from_slice(&value.to_ne_bytes()))
+    }
+
+    fn set_array(cache: &mut UniformCache, loc: GLint, values: &[f32]) -> bool {
+        cache.changed(loc, values.len() as GLsizei, |out| {
+            for value in values {
+                out.extend_from_slice(&value.to_ne_bytes());
+            }
+        })
+    }
+
+    #[test]
+    fn skips_unchanged_values() {
+        let mut cache = UniformCache::default();
+
+        assert!(set(&mut cache, 0, 1.0));
+        assert!(!set(&mut cache, 0, 1.0));
+        assert!(set(&mut cache, 1, 1.0));
+        assert!(set(&mut cache, 0, 2.0));
+        assert!(!set(&mut cache, 0, 2.0));
+        assert_eq!(cache.stats(), UniformStats { uploaded: 3, skipped: 2 });
+
+        cache.clear();
+        assert!(set(&mut cache, 0, 2.0));
+
+        cache.reset_stats();
+        assert_eq!(cache.stats(), UniformStats::default());
+    }
+
+    #[test]
+    fn uploads_values_without_bytes() {
+        let mut cache = UniformCache::default();
+
+        assert!(set(&mut cache, 0, 1.0));
+        assert!(cache.changed(1, 1, |_| {}));
+        assert!(cache.changed(1, 1, |_| {}));
+        assert!(set(&mut cache, 0, 1.0));
+    }
+
+    #[test]
+    fn forgets_overlapping_values() {
+        let mut cache = UniformCache::default();
+
+        assert!(set_array(&mut cache, 0, &[1.0, 2.0, 3.0]));
+        assert!(!set_array(&mut cache, 0, &[1.0, 2.0, 3.0]));
+        assert!(set(&mut cache, 1, 5.0));
+        assert!(set_array(&mut cache, 0, &[1.0, 2.0, 3.0]));
+        assert!(set(&mut cache, 1, 5.0));
+
+        // A shorter array at the same location is a different value
+        assert!(set_array(&mut cache, 0, &[1.0, 2.0]));
+        assert!(set_array(&mut cache, 2, &[3.0, 4.0]));
+        assert!(!set_array(&mut cache, 0, &[1.0, 2.0]));
+        assert!(set_array(&mut cache, 1, &[2.0, 3.0]));
+        assert!(set_array(&mut cache, 0, &[1.0, 2.0]));
+        assert!(set_array(&mut cache, 2, &[3.0, 4.0]));
+    }
+}
@@ -0,0 +1,246 @@
+use gl::types::*;
+use super::*;
+#[cfg(debug_assertions)]
+use super::reflection::find_uniform;
+use anyhow::{Result, bail};
+#[cfg(debug_assertions)]
+use std::rc::Rc;
+
+/// A value that sets one or more uniforms, starting at the uniform `name`.
+///
+/// Implemented for every `UniformType` and by `#[derive(Uniforms)]`, where fields are named
+/// `name.field`, and array fields `name.field[i]`, like GLSL structs and arrays.
+pub trait Uniforms {
+    fn set_uniforms(&self, setter: &mut UniformSetter, name: &str);
+}
+
+/// Passes the uniforms of a value to `Program::set_uniforms`.
+pub struct UniformSetter<'a> {
+    target: &'a mut dyn UniformTarget,
+}
+
+impl<'a> UniformSetter<'a> {
+    pub fn set<T>(&mut self, name: &str, value: &T)
+    where
+        T: UniformType
+    {
+        self.target.set(name, value);
+    }
+}
+
+/// Receives the uniforms passed to a `UniformSetter`.
+trait UniformTarget {
+    fn set(&mut self, name: &str, value: &dyn UniformValue);
+}
+
+/// A `UniformType` that can be passed as a trait object.
+///
+/// Only debug builds check the type of the uniforms.
+trait UniformValue {
+    #[cfg(any(debug_assertions, test))]
+    fn glsl_name(&self) -> &'static str;
+
+    #[cfg(debug_assertions)]
+    fn accepts(&self, ty: GLenum) -> bool;
+
+    /// # Safety
+    /// Requires a current GL context.
+    unsafe fn upload(&self, program: GLuint, loc: GLint);
+
+    fn write_bytes(&self, out: &mut Vec<u8>);
+}
+
+impl<T> UniformValue for T
+where
+    T: UniformType
+{
+    #[cfg(any(debug_assertions, test))]
+    fn glsl_name(&self) -> &'static str {
+        T::GLSL_NAME
+    }
+
+    #[cfg(debug_assertions)]
+    fn accepts(&self, ty: GLenum) -> bool {
+        T::accepts(ty)
+    }
+
+    unsafe fn upload(&self, program: GLuint, loc: GLint) {
+        UniformType::upload(self, program, loc);
+    }
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        UniformType::write_bytes(self, out);
+    }
+}
+
+/// Sets the uniforms of a program.
+///
+/// In debug builds every uniform is checked against the active uniforms of the program.
+struct ProgramTarget<'a> {
+    program: &'a Program,
+    #[cfg(debug_assertions)]
+    active: Rc<Vec<ActiveVariable>>,
+    errors: Vec<String>,
+}
+
+impl<'a> ProgramTarget<'a> {
+    fn new(program: &'a Program) -> ProgramTarget<'a> {
+        ProgramTarget {
+            program,
+            #[cfg(debug_assertions)]
+            active: program.cached_active_uniforms(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl<'a> UniformTarget for ProgramTarget<'a> {
+    fn set(&mut self, name: &str, value: &dyn UniformValue) {
+        #[cfg(debug_assertions)]
+        {
+            match find_uniform(&self.active, name) {
+                None => {
+                    self.errors.push(format!("no active uniform named {}", name));
+                    return;
+                },
+                Some(uniform) if !value.accepts(uniform.ty) => {
+                    self.errors.push(format!(
+                        "{} is declared as {} but is set as {}",
+                        name,
+                        glsl_type_name(uniform.ty),
+                        value.glsl_name()
+                    ));
+                    return;
+                },
+                Some(_) => {},
+            }
+        }
+
+        if let Some(loc) = self.program.get_uniform_loc(name) {
+            if self.program.uniform_cache().borrow_mut().changed(loc, 1, |out| value.write_bytes(out)) {
+                unsafe {
+                    value.upload(self.program.id(), loc);
+                }
+            }
+        }
+    }
+}
+
+impl Program {
+    /// Sets every uniform of the value, usually a struct with `#[derive(Uniforms)]`.
+    ///
+    /// In debug builds fields without a matching active uniform, or with a type that does not match the shader,
+    /// are reported as an error after the remaining uniforms are set. Release builds do not check the uniforms.
+    pub fn set_uniforms<U>(&self, values: &U) -> Result<()>
+    where
+        U: Uniforms
+    {
+        let mut target = ProgramTarget::new(self);
+        values.set_uniforms(&mut UniformSetter { target: &mut target }, "");
+        if !target.errors.is_empty() {
+            bail!("Failed to set uniforms: {}", target.errors.join(", "));
+        }
+        Ok(())
+    }
+}
+
+impl<T> Uniforms for [T]
+where
+    T: Uniforms
+{
+    fn set_uniforms(&self, setter: &mut UniformSetter, name: &str) {
+        for (i, value) in self.iter().enumerate() {
+            value.set_uniforms(setter, &format!("{}[{}]", name, i));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the name and GLSL type of every uniform instead of setting it.
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl UniformTarget for Recorder {
+        fn set(&mut self, name: &str, value: &dyn UniformValue) {
+            self.0.push(format!("{} {}", value.glsl_name(), name));
+        }
+    }
+
+    fn record<U: Uniforms>(values: &U) -> Vec<String> {
+        let mut recorder = Recorder::default();
+        values.set_uniforms(&mut UniformSetter { target: &mut recorder }, "");
+        recorder.0
+    }
+
+    #[derive(Uniforms)]
+    struct Light {
+        color: [f32; 3],
+        intensity: f32,
+    }
+
+    #[derive(Uniforms)]
+    struct Scene {
+        model: [[f32; 4]; 4],
+        normal: [[f32; 3]; 3],
+        lights: [Light; 2],
+        #[uniform(array)]
+        weights: [f32; 2],
+        #[uniform(name = "time")]
+        seconds: f32,
+        #[uniform(skip)]
+        _frame: u32,
+    }
+
+    #[test]
+    fn derive_names_fields_like_glsl() {
+        let light = || Light { color: [1.0; 3], intensity: 1.0 };
+        let scene = Scene {
+            model: [[0.0; 4]; 4],
+            normal: [[0.0; 3]; 3],
+            lights: [light(), light()],
+            weights: [0.5; 2],
+            seconds: 0.0,
+            _frame: 0,
+        };
+
+        assert_eq!(record(&scene), [
+            "mat4 model",
+            "mat3 normal",
+            "vec3 lights[0].color",
+            "float lights[0].intensity",
+            "vec3 lights[1].color",
+            "float lights[1].intensity",
+            "float weights[0]",
+            "float weights[1]",
+            "float time",
+        ]);
+    }
+
+    #[derive(Uniforms)]
+    struct Lights {
+        lights: [Light; 3],
+        ambient: [f32; 3],
+    }
+
+    #[test]
+    fn derive_sets_arrays_of_structs_per_element() {
+        let light = |intensity| Light { color: [0.0; 3], intensity };
+        let lights = Lights {
+            lights: [light(1.0), light(2.0), light(3.0)],
+            ambient: [0.1; 3],
+        };
+
+        assert_eq!(record(&lights), [
+            "vec3 lights[0].color",
+            "float lights[0].intensity",
+            "vec3 lights[1].color",
+            "float lights[1].intensity",
+            "vec3 lights[2].color",
+            "float lights[2].intensity",
+            "vec3 ambient",
+        ]);
+    }
+}
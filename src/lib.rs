@@ -1,10 +1,18 @@
+#![allow(clippy::module_inception)]
+
+// Lets the derive macros refer to the crate as `::simple_gl` from inside it as well
+extern crate self as simple_gl;
+
 pub mod graphics;
 pub mod storage;
-pub(crate) mod error;
+pub mod error;
+
+#[cfg(test)]
+mod testgl;
 
 pub fn get_value<T, F>(mut v: T, f: F) -> T
 where
-    F: FnOnce(&mut T) -> ()
+    F: FnOnce(&mut T)
 {
     f(&mut v);
     v
@@ -1,11 +1,11 @@
 use gl::types::*;
 use std::marker::PhantomData;
-use super::{BufferType, BufferData, BufferAcces, ReadBufferMap, WriteBufferMap, DynamicBuffer};
+use super::{BufferType, BufferAcces, ReadBufferMap, WriteBufferMap, DynamicBuffer};
 use crate::get_value;
 
 pub struct Buffer<T, Kind, Acces>
 where
-    T: Sized + BufferData,
+    T: Sized,
     Kind: BufferType,
     Acces: BufferAcces
 {
@@ -18,7 +18,7 @@ where
 
 impl<T, Kind, Acces> Buffer<T, Kind, Acces>
 where
-    T: Sized + BufferData,
+    T: Sized,
     Kind: BufferType,
     Acces: BufferAcces
 {
@@ -39,7 +39,7 @@ where
         unsafe {
             gl::BufferData(
                 Kind::value(),
-                (data.len() * std::mem::size_of::<T>()) as gl::types::GLsizeiptr,
+                std::mem::size_of_val(data) as gl::types::GLsizeiptr,
                 data as *const [T] as *const GLvoid,
                 Acces::value(),
             );
@@ -65,13 +65,13 @@ where
             gl::BufferSubData(
                 Kind::value(),
                 (offset as usize * std::mem::size_of::<T>()) as isize,
-                (data.len() * std::mem::size_of::<T>()) as gl::types::GLsizeiptr,
+                std::mem::size_of_val(data) as gl::types::GLsizeiptr,
                 data as *const [T] as *const GLvoid
             );
         }
     }
 
-    pub fn read<'a>(&self) -> ReadBufferMap<'_, T, Kind, Acces>{
+    pub fn read(&self) -> ReadBufferMap<'_, T, Kind, Acces>{
         let ptr = unsafe {
             gl::MapBuffer(
                 Kind::value(),
@@ -80,7 +80,7 @@ where
         } as *const T;
 
         let val = unsafe {
-            std::slice::from_raw_parts(ptr as *const T, self.len)
+            std::slice::from_raw_parts(ptr, self.len)
         };
 
         ReadBufferMap {
@@ -94,10 +94,10 @@ where
 
 impl<T, Kind> Buffer<T, Kind, DynamicBuffer> 
 where
-    T: Sized + BufferData,
+    T: Sized,
     Kind: BufferType,
 {
-    pub fn write<'a>(&mut self) -> WriteBufferMap<'_, T, Kind, DynamicBuffer>{
+    pub fn write(&mut self) -> WriteBufferMap<'_, T, Kind, DynamicBuffer>{
         let ptr = unsafe {
             gl::MapBuffer(
                 Kind::value(),
@@ -118,7 +118,7 @@ where
 
 impl<T, Kind, Acces> Drop for Buffer<T, Kind, Acces> 
 where
-    T: Sized + BufferData,
+    T: Sized,
     Kind: BufferType,
     Acces: BufferAcces
 {
@@ -1,8 +1,106 @@
 use super::Primitive;
 use gl::types::*;
+use nalgebra as na;
+
+/// How the shader reads a vertex attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeKind {
+    /// Converted to float, for `float` and `vecN` inputs.
+    Float,
+    /// Integers mapped to [0, 1] or [-1, 1], for `float` and `vecN` inputs.
+    Normalized,
+    /// Kept as integers, for `int`, `uint`, `ivecN` and `uvecN` inputs.
+    Integer,
+    /// Kept as doubles, for `double` and `dvecN` inputs.
+    Double
+}
+
+impl AttributeKind {
+    /// The kind matching the primitive, integers are read as integers and doubles as doubles.
+    pub fn of(primitive: Primitive) -> AttributeKind {
+        match primitive {
+            Primitive::Float | Primitive::HalfFloat | Primitive::Nothing => AttributeKind::Float,
+            // Packed formats can only be read as floats
+            Primitive::Int2101010Rev | Primitive::UInt2101010Rev => AttributeKind::Float,
+            Primitive::Double => AttributeKind::Double,
+            _ => AttributeKind::Integer,
+        }
+    }
+}
+
+/// A vertex attribute of a `BufferData` value, `count` values of the primitive starting at `offset` bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute {
+    pub primitive: Primitive,
+    pub count: GLuint,
+    pub offset: GLuint,
+    pub kind: AttributeKind,
+}
+
+/// Reads the integer attributes of `T` as normalized floats, e.g. `Normalized<[u8; 4]>` for a color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct Normalized<T>(pub T);
+
+impl<T> BufferData for Normalized<T>
+where
+    T: BufferData
+{
+    fn prototype() -> Vec<(Primitive, GLuint)> {
+        T::prototype()
+    }
+
+    fn layout() -> Vec<Attribute> {
+        T::layout()
+            .into_iter()
+            .map(|attribute| Attribute {
+                kind: AttributeKind::Normalized,
+                ..attribute
+            })
+            .collect()
+    }
+}
 
 pub trait BufferData {
     fn prototype() -> Vec<(Primitive, GLuint)>;
+
+    /// The attributes of the value, each bound to its own location by `Vao::bind_vbo`.
+    ///
+    /// By default the prototype is assumed to be packed without padding.
+    /// `#[derive(BufferData)]` uses the real field offsets instead.
+    fn layout() -> Vec<Attribute> {
+        let mut offset = 0;
+        Self::prototype()
+            .into_iter()
+            .map(|(primitive, count)| {
+                let attribute = Attribute {
+                    primitive,
+                    count,
+                    offset,
+                    kind: AttributeKind::of(primitive),
+                };
+                offset += primitive.bytes(count);
+                attribute
+            })
+            .collect()
+    }
+}
+
+/// Describes the layout as a prototype, with the padding between attributes as `Primitive::Nothing`.
+pub fn packed_prototype(layout: &[Attribute], object_size: GLuint) -> Vec<(Primitive, GLuint)> {
+    let mut prototype = Vec::new();
+    let mut end = 0;
+    for attribute in layout {
+        if attribute.offset > end {
+            prototype.push((Primitive::Nothing, attribute.offset - end));
+        }
+        prototype.push((attribute.primitive, attribute.count));
+        end = attribute.offset + attribute.primitive.bytes(attribute.count);
+    }
+    if object_size > end {
+        prototype.push((Primitive::Nothing, object_size - end));
+    }
+    prototype
 }
 
 macro_rules! bufferdata_impl {
@@ -44,8 +142,220 @@ macro_rules! bufferdata_impl {
 }
 
 bufferdata_impl!{
+    4, i8, Byte;
+    4, u8, UByte;
+    4, i16, Short;
+    4, u16, UShort;
     4, i32, Int;
     4, u32, UInt;
     4, f32, Float;
     4, f64, Double;
 }
+
+#[cfg(feature = "half")]
+bufferdata_impl!{
+    4, half::f16, HalfFloat;
+}
+
+/// Four signed components in one `u32`, 10 bits for x, y and z and 2 bits for w.
+/// Usually read as `Normalized<PackedInt2101010>`, e.g. for normals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct PackedInt2101010(pub u32);
+
+impl PackedInt2101010 {
+    /// Packs components in [-1, 1] to be read back with `Normalized`.
+    pub fn from_normalized(x: f32, y: f32, z: f32, w: f32) -> PackedInt2101010 {
+        let pack = |value: f32, max: f32, bits: u32| {
+            ((value.clamp(-1.0, 1.0) * max).round() as i32 as u32) & ((1 << bits) - 1)
+        };
+        PackedInt2101010(
+            pack(x, 511.0, 10)
+                | pack(y, 511.0, 10) << 10
+                | pack(z, 511.0, 10) << 20
+                | pack(w, 1.0, 2) << 30
+        )
+    }
+}
+
+impl BufferData for PackedInt2101010 {
+    fn prototype() -> Vec<(Primitive, GLuint)> {
+        vec![(Primitive::Int2101010Rev, 4)]
+    }
+}
+
+/// Four unsigned components in one `u32`, 10 bits for x, y and z and 2 bits for w.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct PackedUInt2101010(pub u32);
+
+impl PackedUInt2101010 {
+    /// Packs components in [0, 1] to be read back with `Normalized`.
+    pub fn from_normalized(x: f32, y: f32, z: f32, w: f32) -> PackedUInt2101010 {
+        let pack = |value: f32, max: f32| (value.clamp(0.0, 1.0) * max).round() as u32;
+        PackedUInt2101010(
+            pack(x, 1023.0)
+                | pack(y, 1023.0) << 10
+                | pack(z, 1023.0) << 20
+                | pack(w, 3.0) << 30
+        )
+    }
+}
+
+impl BufferData for PackedUInt2101010 {
+    fn prototype() -> Vec<(Primitive, GLuint)> {
+        vec![(Primitive::UInt2101010Rev, 4)]
+    }
+}
+
+macro_rules! bufferdata_nalgebra {
+    (vector, $n:expr, $($ty:ident),+; $($t:tt)*) => {
+        $(
+            impl<T> BufferData for na::$ty<T>
+            where
+                T: BufferData + na::Scalar
+            {
+                fn prototype() -> Vec<(Primitive, GLuint)> {
+                    T::prototype().into_iter().map(|(primitive, count)| (primitive, count * $n)).collect()
+                }
+            }
+        )+
+        bufferdata_nalgebra!{$($t)*}
+    };
+    // Matrices take one location per column, like GLSL matrix attributes
+    (matrix, $n:expr, $ty:ident; $($t:tt)*) => {
+        impl<T> BufferData for na::$ty<T>
+        where
+            T: BufferData + na::Scalar
+        {
+            fn prototype() -> Vec<(Primitive, GLuint)> {
+                na::Vector::<T, na::Const<$n>, na::ArrayStorage<T, $n, 1>>::prototype().repeat($n)
+            }
+        }
+        bufferdata_nalgebra!{$($t)*}
+    };
+    () => {};
+}
+
+bufferdata_nalgebra!{
+    vector, 2, Vector2, Point2;
+    vector, 3, Vector3, Point3;
+    vector, 4, Vector4, Point4;
+    matrix, 2, Matrix2;
+    matrix, 3, Matrix3;
+    matrix, 4, Matrix4;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::buffer::BufferData;
+
+    #[derive(BufferData)]
+    #[repr(C)]
+    struct Vertex {
+        position: [f32; 3],
+        weight: f64,
+        uv: na::Vector2<f32>,
+        bones: [[u32; 2]; 2],
+        transform: na::Matrix2<f32>,
+    }
+
+    #[derive(BufferData)]
+    #[repr(C)]
+    struct PathVertex {
+        color: [core::primitive::u8; 4],
+        position: [std::primitive::f32; 2],
+    }
+
+    fn attribute(primitive: Primitive, count: GLuint, offset: GLuint) -> Attribute {
+        Attribute {
+            primitive,
+            count,
+            offset,
+            kind: AttributeKind::of(primitive),
+        }
+    }
+
+    #[test]
+    fn default_layout_is_packed() {
+        assert_eq!(<[f32; 3]>::layout(), vec![attribute(Primitive::Float, 3, 0)]);
+        assert_eq!(na::Vector4::<i32>::layout(), vec![attribute(Primitive::Int, 4, 0)]);
+    }
+
+    #[test]
+    fn derived_layout_uses_field_offsets() {
+        assert_eq!(Vertex::layout(), vec![
+            attribute(Primitive::Float, 3, 0),
+            attribute(Primitive::Double, 1, 16),
+            attribute(Primitive::Float, 2, 24),
+            attribute(Primitive::UInt, 2, 32),
+            attribute(Primitive::UInt, 2, 40),
+            attribute(Primitive::Float, 2, 48),
+            attribute(Primitive::Float, 2, 56),
+        ]);
+    }
+
+    #[test]
+    fn attribute_kinds() {
+        let kinds: Vec<_> = Vertex::layout().iter().map(|attribute| attribute.kind).collect();
+        assert_eq!(kinds, vec![
+            AttributeKind::Float,
+            AttributeKind::Double,
+            AttributeKind::Float,
+            AttributeKind::Integer,
+            AttributeKind::Integer,
+            AttributeKind::Float,
+            AttributeKind::Float,
+        ]);
+
+        let normalized = Normalized::<[u32; 4]>::layout();
+        assert_eq!(normalized[0].kind, AttributeKind::Normalized);
+        assert_eq!(normalized[0].primitive, Primitive::UInt);
+    }
+
+    #[test]
+    fn small_components() {
+        assert_eq!(Normalized::<[u8; 4]>::layout(), vec![Attribute {
+            primitive: Primitive::UByte,
+            count: 4,
+            offset: 0,
+            kind: AttributeKind::Normalized,
+        }]);
+        assert_eq!(<[i16; 2]>::layout(), vec![attribute(Primitive::Short, 2, 0)]);
+        assert_eq!(<[i16; 2]>::layout()[0].kind, AttributeKind::Integer);
+    }
+
+    #[test]
+    fn derived_layout_accepts_scalar_paths() {
+        assert_eq!(PathVertex::layout(), vec![
+            attribute(Primitive::UByte, 4, 0),
+            attribute(Primitive::Float, 2, 4),
+        ]);
+    }
+
+    #[test]
+    fn packed_components() {
+        let layout = PackedInt2101010::layout();
+        assert_eq!(layout, vec![attribute(Primitive::Int2101010Rev, 4, 0)]);
+        assert_eq!(layout[0].primitive.bytes(layout[0].count), 4);
+
+        assert_eq!(PackedInt2101010::from_normalized(1.0, -1.0, 0.0, 1.0).0, 0x1ff | 0x201 << 10 | 1 << 30);
+        assert_eq!(PackedUInt2101010::from_normalized(1.0, 0.0, 0.5, 1.0).0, 0x3ff | 512 << 20 | 3 << 30);
+    }
+
+    #[test]
+    fn derived_prototype_marks_padding() {
+        assert_eq!(std::mem::size_of::<Vertex>(), 64);
+        assert_eq!(Vertex::prototype(), vec![
+            (Primitive::Float, 3),
+            (Primitive::Nothing, 4),
+            (Primitive::Double, 1),
+            (Primitive::Float, 2),
+            (Primitive::UInt, 2),
+            (Primitive::UInt, 2),
+            (Primitive::Float, 2),
+            (Primitive::Float, 2),
+        ]);
+    }
+}
@@ -7,6 +7,11 @@ pub trait BufferType {
 
 pub struct ElementArrayBuffer();
 pub struct ArrayBuffer();
+pub struct DispatchIndirectBuffer();
+pub struct UniformBuffer();
+pub struct ShaderStorageBuffer();
+pub struct TransformFeedbackBuffer();
+pub struct DrawIndirectBuffer();
 
 impl BufferType for ArrayBuffer {
     fn value() -> GLenum {
@@ -20,6 +25,36 @@ impl BufferType for ElementArrayBuffer {
     }
 }
 
+impl BufferType for DispatchIndirectBuffer {
+    fn value() -> GLenum {
+        gl::DISPATCH_INDIRECT_BUFFER
+    }
+}
+
+impl BufferType for UniformBuffer {
+    fn value() -> GLenum {
+        gl::UNIFORM_BUFFER
+    }
+}
+
+impl BufferType for ShaderStorageBuffer {
+    fn value() -> GLenum {
+        gl::SHADER_STORAGE_BUFFER
+    }
+}
+
+impl BufferType for TransformFeedbackBuffer {
+    fn value() -> GLenum {
+        gl::TRANSFORM_FEEDBACK_BUFFER
+    }
+}
+
+impl BufferType for DrawIndirectBuffer {
+    fn value() -> GLenum {
+        gl::DRAW_INDIRECT_BUFFER
+    }
+}
+
 // BUFFER ACCES
 pub trait BufferAcces {
     fn value() -> GLenum;
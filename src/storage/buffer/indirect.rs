@@ -0,0 +1,116 @@
+use gl::types::*;
+use super::{Buffer, BufferAcces, BufferType, DrawIndirectBuffer, ShaderStorageBuffer, Primitive, Vao, Std430};
+use super::std430::AssertStd430;
+
+/// The parameters of one draw in a `multi_draw_arrays_indirect` call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Std430)]
+#[repr(C)]
+pub struct DrawArraysIndirectCommand {
+    pub count: GLuint,
+    pub instance_count: GLuint,
+    pub first: GLuint,
+    pub base_instance: GLuint,
+}
+
+/// The parameters of one draw in a `multi_draw_elements_indirect` call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Std430)]
+#[repr(C)]
+pub struct DrawElementsIndirectCommand {
+    pub count: GLuint,
+    pub instance_count: GLuint,
+    pub first_index: GLuint,
+    pub base_vertex: GLint,
+    pub base_instance: GLuint,
+}
+
+/// The work group counts of one `ComputeProgram::dispatch_indirect` call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Std430)]
+#[repr(C)]
+pub struct DispatchIndirectCommand {
+    pub num_groups_x: GLuint,
+    pub num_groups_y: GLuint,
+    pub num_groups_z: GLuint,
+}
+
+impl<T, Acces> Buffer<T, DrawIndirectBuffer, Acces>
+where
+    T: Sized + Std430,
+    Acces: BufferAcces
+{
+    /// Binds the commands to a shader storage block binding point, so a compute shader can write them.
+    pub fn bind_storage(&self, binding: GLuint) {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertStd430::<T>::STRIDE;
+        unsafe {
+            gl::BindBufferBase(ShaderStorageBuffer::value(), binding, self.id);
+        }
+    }
+}
+
+impl Vao {
+    /// Draws `count` commands from the buffer starting at the element `first`.
+    pub fn multi_draw_arrays_indirect<Acces>(
+        &mut self,
+        commands: &Buffer<DrawArraysIndirectCommand, DrawIndirectBuffer, Acces>,
+        first: GLuint,
+        count: GLuint
+    )
+    where
+        Acces: BufferAcces
+    {
+        self.bind();
+        commands.bind();
+        self.apply_patch_vertices();
+        unsafe {
+            gl::MultiDrawArraysIndirect(
+                self.format.value(),
+                (first as usize * std::mem::size_of::<DrawArraysIndirectCommand>()) as *const GLvoid,
+                count as GLsizei,
+                0
+            );
+        }
+    }
+
+    /// Draws `count` commands from the buffer starting at the element `first`,
+    /// with indices of type `ty` from the bound element buffer.
+    pub fn multi_draw_elements_indirect<Acces>(
+        &mut self,
+        ty: Primitive,
+        commands: &Buffer<DrawElementsIndirectCommand, DrawIndirectBuffer, Acces>,
+        first: GLuint,
+        count: GLuint
+    )
+    where
+        Acces: BufferAcces
+    {
+        self.bind();
+        commands.bind();
+        self.apply_patch_vertices();
+        unsafe {
+            gl::MultiDrawElementsIndirect(
+                self.format.value(),
+                ty.value(),
+                (first as usize * std::mem::size_of::<DrawElementsIndirectCommand>()) as *const GLvoid,
+                count as GLsizei,
+                0
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::buffer::std430_stride;
+
+    // GL reads the commands tightly packed, which a compute shader writing them with std430 has to match
+    #[test]
+    fn commands_are_tightly_packed() {
+        assert_eq!(std::mem::size_of::<DrawArraysIndirectCommand>(), 16);
+        assert_eq!(std430_stride::<DrawArraysIndirectCommand>(), 16);
+        assert_eq!(std::mem::size_of::<DrawElementsIndirectCommand>(), 20);
+        assert_eq!(std430_stride::<DrawElementsIndirectCommand>(), 20);
+        assert_eq!(std::mem::size_of::<DispatchIndirectCommand>(), 12);
+        assert_eq!(std430_stride::<DispatchIndirectCommand>(), 12);
+    }
+}
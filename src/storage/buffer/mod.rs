@@ -7,6 +7,11 @@ mod buffertype;
 mod readbuffermap;
 mod writebuffermap;
 mod bufferdata;
+mod std140;
+mod std430;
+mod ubo;
+mod transformfeedback;
+mod indirect;
 
 pub use buffer::*;
 pub use bufferdata::*;
@@ -16,6 +21,13 @@ pub use format::Format;
 pub use primitive::Primitive;
 pub use buffertype::*;
 pub use vao::Vao;
+pub use std140::{Std140, align_to, max_align};
+pub use ubo::UboBuffer;
+pub use transformfeedback::TransformFeedback;
+pub use indirect::{DrawArraysIndirectCommand, DrawElementsIndirectCommand, DispatchIndirectCommand};
+pub use std430::{Std430, std430_stride};
+pub use simple_gl_derive::{Std140, Std430, BufferData};
 
 pub type VboBuffer<T, Acces> = Buffer<T, ArrayBuffer, Acces>;
-pub type EboBuffer<T, Acces> = Buffer<T, ElementArrayBuffer, Acces>;
\ No newline at end of file
+pub type EboBuffer<T, Acces> = Buffer<T, ElementArrayBuffer, Acces>;
+pub type SsboBuffer<T, Acces> = Buffer<T, ShaderStorageBuffer, Acces>;
\ No newline at end of file
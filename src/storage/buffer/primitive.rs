@@ -1,13 +1,20 @@
 use gl::types::*;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Primitive {
     Byte,
+    UByte,
     Short,
     UShort,
     Int,
     UInt,
     Float,
     Double,
+    HalfFloat,
+    /// Four signed components packed into 32 bits, see `PackedInt2101010`.
+    Int2101010Rev,
+    /// Four unsigned components packed into 32 bits, see `PackedUInt2101010`.
+    UInt2101010Rev,
     Nothing
 }
 
@@ -15,27 +22,43 @@ impl Primitive {
     pub fn value(&self) -> GLenum {
         match self {
             Primitive::Byte => gl::BYTE,
+            Primitive::UByte => gl::UNSIGNED_BYTE,
             Primitive::Short => gl::SHORT,
             Primitive::UShort => gl::UNSIGNED_SHORT,
             Primitive::Int => gl::INT,
             Primitive::UInt => gl::UNSIGNED_INT,
             Primitive::Float => gl::FLOAT,
             Primitive::Double => gl::DOUBLE,
+            Primitive::HalfFloat => gl::HALF_FLOAT,
+            Primitive::Int2101010Rev => gl::INT_2_10_10_10_REV,
+            Primitive::UInt2101010Rev => gl::UNSIGNED_INT_2_10_10_10_REV,
             Primitive::Nothing => unimplemented!()
         }
     }
 
     pub fn size(&self) -> GLuint {
         let size = match self {
-            Primitive::Byte => std::mem::size_of::<u8>(),
+            Primitive::Byte => std::mem::size_of::<i8>(),
+            Primitive::UByte => std::mem::size_of::<u8>(),
             Primitive::Short => std::mem::size_of::<i16>(),
             Primitive::UShort => std::mem::size_of::<u16>(),
             Primitive::Int => std::mem::size_of::<i32>(),
             Primitive::UInt => std::mem::size_of::<u32>(),
             Primitive::Float => std::mem::size_of::<f32>(),
             Primitive::Double => std::mem::size_of::<f64>(),
+            Primitive::HalfFloat => std::mem::size_of::<u16>(),
+            Primitive::Int2101010Rev => std::mem::size_of::<u32>(),
+            Primitive::UInt2101010Rev => std::mem::size_of::<u32>(),
             Primitive::Nothing => 1
         };
         size as GLuint
     }
+
+    /// The size in bytes of `count` components, packed formats hold all components in one value.
+    pub fn bytes(&self, count: GLuint) -> GLuint {
+        match self {
+            Primitive::Int2101010Rev | Primitive::UInt2101010Rev => self.size(),
+            _ => self.size() * count,
+        }
+    }
 }
\ No newline at end of file
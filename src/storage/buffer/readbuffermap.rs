@@ -4,7 +4,7 @@ use super::*;
 
 pub struct ReadBufferMap<'a, T, Kind, Acces> 
 where
-    T: Sized + BufferData,
+    T: Sized,
     Kind: BufferType,
     Acces: BufferAcces
 {
@@ -14,7 +14,7 @@ where
 
 impl<T, Kind, Acces> Deref for ReadBufferMap<'_, T, Kind, Acces> 
 where
-    T: Sized + BufferData,
+    T: Sized,
     Kind: BufferType,
     Acces: BufferAcces
 {
@@ -27,7 +27,7 @@ where
 
 impl<T, Kind, Acces> Drop for ReadBufferMap<'_, T, Kind, Acces> 
 where
-    T: Sized + BufferData,
+    T: Sized,
     Kind: BufferType,
     Acces: BufferAcces
 {
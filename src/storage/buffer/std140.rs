@@ -0,0 +1,209 @@
+use nalgebra as na;
+
+/// A type that can be written into a uniform block with the std140 layout rules.
+///
+/// Vectors and matrices are nalgebra types, Rust arrays are GLSL arrays, so `[f32; 4]` is
+/// `float[4]` with a 16 byte stride and not a `vec4`. Structs implement it with `#[derive(Std140)]`,
+/// which can check the size of the block at compile time with `#[std140(size = N)]`.
+pub trait Std140 {
+    /// The base alignment in bytes.
+    const ALIGN: usize;
+    /// The number of bytes written by `write_std140`. Arrays and matrices include the padding of every element
+    /// to a multiple of 16 bytes and structs are rounded up to their alignment, but a `vec3` is only 12 bytes.
+    const SIZE: usize;
+
+    /// Writes the value into `out`, which is exactly `SIZE` bytes long.
+    fn write_std140(&self, out: &mut [u8]);
+
+    fn to_std140(&self) -> Vec<u8> {
+        let mut out = vec![0; Self::SIZE];
+        self.write_std140(&mut out);
+        out
+    }
+}
+
+/// Rounds the offset up to a multiple of the alignment.
+pub const fn align_to(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+pub const fn max_align(a: usize, b: usize) -> usize {
+    if a > b { a } else { b }
+}
+
+macro_rules! std140_scalar {
+    ($($ty:ty, $size:expr, $to_bytes:expr;)+) => {
+        $(
+            impl Std140 for $ty {
+                const ALIGN: usize = $size;
+                const SIZE: usize = $size;
+
+                fn write_std140(&self, out: &mut [u8]) {
+                    let to_bytes: fn(&$ty) -> [u8; $size] = $to_bytes;
+                    out.copy_from_slice(&to_bytes(self));
+                }
+            }
+        )+
+    };
+}
+
+std140_scalar! {
+    f32, 4, |v| v.to_ne_bytes();
+    i32, 4, |v| v.to_ne_bytes();
+    u32, 4, |v| v.to_ne_bytes();
+    bool, 4, |v| (*v as u32).to_ne_bytes();
+    f64, 8, |v| v.to_ne_bytes();
+}
+
+macro_rules! std140_vector {
+    ($($ty:ty, $n:expr, $align:expr;)+) => {
+        $(
+            impl<T> Std140 for $ty
+            where
+                T: Std140 + na::Scalar
+            {
+                const ALIGN: usize = T::SIZE * $align;
+                const SIZE: usize = T::SIZE * $n;
+
+                fn write_std140(&self, out: &mut [u8]) {
+                    for (i, component) in self.iter().enumerate() {
+                        component.write_std140(&mut out[i * T::SIZE..(i + 1) * T::SIZE]);
+                    }
+                }
+            }
+        )+
+    };
+}
+
+// std140 only pads vectors between elements of arrays, a vec3 is 12 bytes with the alignment of a vec4
+std140_vector! {
+    na::Vector2<T>, 2, 2;
+    na::Vector3<T>, 3, 4;
+    na::Vector4<T>, 4, 4;
+}
+
+macro_rules! std140_matrix {
+    ($($ty:ty, $column:ty, $cols:expr;)+) => {
+        $(
+            // Each column is padded to a vec4, like the elements of a std140 array
+            impl<T> Std140 for $ty
+            where
+                T: Std140 + na::Scalar
+            {
+                const ALIGN: usize = align_to(<$column>::ALIGN, 16);
+                const SIZE: usize = align_to(<$column>::SIZE, Self::ALIGN) * $cols;
+
+                fn write_std140(&self, out: &mut [u8]) {
+                    let stride = align_to(<$column>::SIZE, Self::ALIGN);
+                    for (i, column) in self.column_iter().enumerate() {
+                        let column: $column = column.into_owned();
+                        column.write_std140(&mut out[i * stride..i * stride + <$column>::SIZE]);
+                    }
+                }
+            }
+        )+
+    };
+}
+
+std140_matrix! {
+    na::Matrix2<T>, na::Vector2<T>, 2;
+    na::Matrix3<T>, na::Vector3<T>, 3;
+    na::Matrix4<T>, na::Vector4<T>, 4;
+}
+
+// Array elements are padded to a multiple of a vec4
+impl<T, const N: usize> Std140 for [T; N]
+where
+    T: Std140
+{
+    const ALIGN: usize = align_to(T::ALIGN, 16);
+    const SIZE: usize = align_to(T::SIZE, Self::ALIGN) * N;
+
+    fn write_std140(&self, out: &mut [u8]) {
+        let stride = align_to(T::SIZE, Self::ALIGN);
+        for (i, element) in self.iter().enumerate() {
+            element.write_std140(&mut out[i * stride..i * stride + T::SIZE]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Matrix3, Vector3};
+    use std::convert::TryInto;
+    use crate::storage::buffer::Std140;
+
+    fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+        f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn mat3_columns_have_a_vec4_stride() {
+        assert_eq!((Matrix3::<f32>::ALIGN, Matrix3::<f32>::SIZE), (16, 48));
+
+        let matrix = Matrix3::<f32>::new(
+            1.0, 2.0, 3.0,
+            4.0, 5.0, 6.0,
+            7.0, 8.0, 9.0,
+        );
+        let bytes = matrix.to_std140();
+        // The first column, then padding, then the second column
+        assert_eq!([read_f32(&bytes, 0), read_f32(&bytes, 4), read_f32(&bytes, 8)], [1.0, 4.0, 7.0]);
+        assert_eq!(read_f32(&bytes, 12), 0.0);
+        assert_eq!([read_f32(&bytes, 16), read_f32(&bytes, 20), read_f32(&bytes, 24)], [2.0, 5.0, 8.0]);
+        assert_eq!(read_f32(&bytes, 32), 3.0);
+    }
+
+    #[test]
+    fn float_arrays_have_a_vec4_stride() {
+        assert_eq!(<[f32; 3]>::SIZE, 48);
+
+        let bytes = [1.0f32, 2.0, 3.0].to_std140();
+        assert_eq!([read_f32(&bytes, 0), read_f32(&bytes, 16), read_f32(&bytes, 32)], [1.0, 2.0, 3.0]);
+        assert_eq!(read_f32(&bytes, 4), 0.0);
+    }
+
+    #[derive(Std140)]
+    struct Light {
+        color: Vector3<f32>,
+        intensity: f32,
+    }
+
+    #[derive(Std140)]
+    struct Inner {
+        value: f32,
+    }
+
+    #[derive(Std140)]
+    struct Outer {
+        first: f32,
+        inner: Inner,
+        last: f32,
+    }
+
+    #[test]
+    fn float_packs_after_vec3() {
+        assert_eq!((Light::ALIGN, Light::SIZE), (16, 16));
+
+        let light = Light {
+            color: Vector3::new(1.0, 2.0, 3.0),
+            intensity: 4.0,
+        };
+        let bytes = light.to_std140();
+        assert_eq!([read_f32(&bytes, 8), read_f32(&bytes, 12)], [3.0, 4.0]);
+    }
+
+    #[test]
+    fn nested_structs_are_rounded_to_a_vec4() {
+        assert_eq!((Inner::ALIGN, Inner::SIZE), (16, 16));
+        assert_eq!(Outer::SIZE, 48);
+
+        let outer = Outer {
+            first: 1.0,
+            inner: Inner { value: 2.0 },
+            last: 3.0,
+        };
+        let bytes = outer.to_std140();
+        assert_eq!([read_f32(&bytes, 0), read_f32(&bytes, 16), read_f32(&bytes, 32)], [1.0, 2.0, 3.0]);
+    }
+}
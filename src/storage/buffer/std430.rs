@@ -0,0 +1,217 @@
+use gl::types::*;
+use nalgebra as na;
+use super::{Buffer, BufferAcces, BufferType, ShaderStorageBuffer, align_to};
+
+/// The std430 alignment and size of a type that is shared with shaders through a shader storage buffer.
+///
+/// Unlike `Std140` the value is not converted when uploaded, so the Rust layout of the type has to
+/// match the std430 layout. `#[derive(Std430)]` checks every field offset and the struct size at compile time,
+/// generic structs once bound, and `Buffer::bind_base` checks the element stride of the buffer.
+/// Vectors and matrices are nalgebra types, Rust arrays are GLSL arrays.
+pub trait Std430 {
+    /// The base alignment in bytes.
+    const ALIGN: usize;
+    /// The size of the value in a buffer. Arrays and matrices are their element stride times
+    /// the number of elements, a `vec3` is 12 bytes and only padded to 16 as an array element.
+    const SIZE: usize;
+    /// Fails to compile when the Rust layout of a derived struct differs from std430.
+    /// Generic structs are checked for each element type a buffer is bound with.
+    #[doc(hidden)]
+    const LAYOUT: () = ();
+}
+
+/// The distance in bytes between elements of an array of `T`.
+pub const fn std430_stride<T: Std430>() -> usize {
+    align_to(T::SIZE, T::ALIGN)
+}
+
+macro_rules! std430_scalar {
+    ($($ty:ty, $size:expr;)+) => {
+        $(
+            impl Std430 for $ty {
+                const ALIGN: usize = $size;
+                const SIZE: usize = $size;
+            }
+        )+
+    };
+}
+
+std430_scalar! {
+    f32, 4;
+    i32, 4;
+    u32, 4;
+    f64, 8;
+}
+
+macro_rules! std430_vector {
+    ($($ty:ty, $n:expr, $align:expr;)+) => {
+        $(
+            impl<T> Std430 for $ty
+            where
+                T: Std430 + na::Scalar
+            {
+                const ALIGN: usize = T::SIZE * $align;
+                const SIZE: usize = T::SIZE * $n;
+            }
+        )+
+    };
+}
+
+// std430 keeps the vec4 alignment of a vec3, so a following vec3 starts 16 bytes later
+std430_vector! {
+    na::Vector2<T>, 2, 2;
+    na::Vector3<T>, 3, 4;
+    na::Vector4<T>, 4, 4;
+}
+
+macro_rules! std430_matrix {
+    ($($ty:ty, $column:ty, $cols:expr;)+) => {
+        $(
+            // Columns are spaced by their std430 array stride, so only vec3 columns are padded
+            impl<T> Std430 for $ty
+            where
+                T: Std430 + na::Scalar
+            {
+                const ALIGN: usize = <$column>::ALIGN;
+                const SIZE: usize = std430_stride::<$column>() * $cols;
+            }
+        )+
+    };
+}
+
+std430_matrix! {
+    na::Matrix2<T>, na::Vector2<T>, 2;
+    na::Matrix3<T>, na::Vector3<T>, 3;
+    na::Matrix4<T>, na::Vector4<T>, 4;
+}
+
+impl<T, const N: usize> Std430 for [T; N]
+where
+    T: Std430
+{
+    const ALIGN: usize = T::ALIGN;
+    const SIZE: usize = std430_stride::<T>() * N;
+}
+
+/// Fails to compile when the Rust stride of `T` differs from its std430 array stride.
+pub(crate) struct AssertStd430<T>(std::marker::PhantomData<T>);
+
+impl<T> AssertStd430<T>
+where
+    T: Std430
+{
+    pub(crate) const STRIDE: () = {
+        let () = T::LAYOUT;
+        assert!(
+            std::mem::size_of::<T>() == std430_stride::<T>() && std::mem::align_of::<T>() <= T::ALIGN,
+            "the Rust layout of the buffer element does not match its std430 layout"
+        );
+    };
+}
+
+impl<T, Acces> Buffer<T, ShaderStorageBuffer, Acces>
+where
+    T: Sized + Std430,
+    Acces: BufferAcces
+{
+    /// Binds the buffer to the shader storage block binding point.
+    pub fn bind_base(&self, binding: GLuint) {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertStd430::<T>::STRIDE;
+        unsafe {
+            gl::BindBufferBase(ShaderStorageBuffer::value(), binding, self.id);
+        }
+    }
+
+    /// Binds `len` elements starting at element `offset` to the shader storage block binding point.
+    pub fn bind_range(&self, binding: GLuint, offset: usize, len: usize) {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertStd430::<T>::STRIDE;
+        let size = std::mem::size_of::<T>();
+        unsafe {
+            gl::BindBufferRange(
+                ShaderStorageBuffer::value(),
+                binding,
+                self.id,
+                (offset * size) as GLintptr,
+                (len * size) as GLsizeiptr
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{Matrix3, Matrix4, Vector2, Vector3, Vector4};
+    use crate::storage::buffer::Std430;
+
+    #[test]
+    fn scalars_and_vectors() {
+        assert_eq!((f32::ALIGN, f32::SIZE), (4, 4));
+        assert_eq!((f64::ALIGN, f64::SIZE), (8, 8));
+        assert_eq!((Vector2::<f32>::ALIGN, Vector2::<f32>::SIZE), (8, 8));
+        assert_eq!((Vector3::<f32>::ALIGN, Vector3::<f32>::SIZE), (16, 12));
+        assert_eq!((Vector4::<f32>::ALIGN, Vector4::<f32>::SIZE), (16, 16));
+        assert_eq!((Vector3::<f64>::ALIGN, Vector3::<f64>::SIZE), (32, 24));
+    }
+
+    #[test]
+    fn arrays_are_not_padded_to_vec4() {
+        assert_eq!(std430_stride::<f32>(), 4);
+        assert_eq!(<[f32; 5]>::SIZE, 20);
+        assert_eq!(<[Vector2<f32>; 3]>::SIZE, 24);
+        // vec3 elements are still padded to their alignment
+        assert_eq!(std430_stride::<Vector3<f32>>(), 16);
+        assert_eq!(<[Vector3<f32>; 2]>::SIZE, 32);
+    }
+
+    #[test]
+    fn matrices() {
+        assert_eq!((Matrix3::<f32>::ALIGN, Matrix3::<f32>::SIZE), (16, 48));
+        assert_eq!((Matrix4::<f32>::ALIGN, Matrix4::<f32>::SIZE), (16, 64));
+    }
+
+    #[derive(Std430)]
+    #[repr(C)]
+    struct Particle {
+        position: Vector3<f32>,
+        mass: f32,
+        velocity: Vector3<f32>,
+        age: f32,
+    }
+
+    // The trailing padding has to be spelled out, the Rust struct is only aligned to 4 bytes
+    #[derive(Std430)]
+    #[repr(C)]
+    struct Cull {
+        bounds: [Vector4<f32>; 2],
+        lod: Vector2<u32>,
+        index: u32,
+        _padding: u32,
+    }
+
+    #[test]
+    fn structs() {
+        assert_eq!((Particle::ALIGN, Particle::SIZE), (16, 32));
+        assert_eq!(std::mem::size_of::<Particle>(), std430_stride::<Particle>());
+
+        assert_eq!((Cull::ALIGN, Cull::SIZE), (16, 48));
+        assert_eq!(std430_stride::<Cull>(), 48);
+    }
+
+    // Checked for each element type, `Pair<Vector3<f32>>` would fail to compile where it is bound
+    #[derive(Std430)]
+    #[repr(C)]
+    struct Pair<T: Std430> {
+        first: T,
+        second: T,
+    }
+
+    #[test]
+    fn generic_structs() {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertStd430::<Pair<Vector2<f32>>>::STRIDE;
+        assert_eq!((Pair::<Vector2<f32>>::ALIGN, Pair::<Vector2<f32>>::SIZE), (8, 16));
+    }
+}
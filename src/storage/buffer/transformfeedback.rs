@@ -0,0 +1,119 @@
+use gl::types::*;
+use super::{Buffer, BufferAcces, BufferType, Format, Primitive, TransformFeedbackBuffer, Vao};
+use crate::get_value;
+
+use anyhow::{Result, bail};
+
+impl<T, Acces> Buffer<T, TransformFeedbackBuffer, Acces>
+where
+    T: Sized,
+    Acces: BufferAcces
+{
+    /// Captures the varyings of the given index into the buffer,
+    /// index 0 when the program uses interleaved varyings.
+    pub fn bind_base(&self, index: GLuint) {
+        unsafe {
+            gl::BindBufferBase(TransformFeedbackBuffer::value(), index, self.id);
+        }
+    }
+
+    /// Captures into `len` elements starting at element `offset`.
+    pub fn bind_range(&self, index: GLuint, offset: usize, len: usize) {
+        let size = std::mem::size_of::<T>();
+        unsafe {
+            gl::BindBufferRange(
+                TransformFeedbackBuffer::value(),
+                index,
+                self.id,
+                (offset * size) as GLintptr,
+                (len * size) as GLsizeiptr
+            );
+        }
+    }
+}
+
+/// Active transform feedback, the output of draws made through it is captured into the bound
+/// `TransformFeedbackBuffer`s. Capturing stops when `end` is called or the guard is dropped.
+pub struct TransformFeedback<'a> {
+    vao: &'a mut Vao,
+    query: GLuint,
+    ended: bool,
+}
+
+impl Vao {
+    /// Starts capturing primitives of the format, which has to be the output primitive of the last
+    /// vertex processing stage. Only points, lines and triangles can be captured.
+    pub fn begin_transform_feedback(&mut self, format: Format) -> Result<TransformFeedback<'_>> {
+        match format {
+            Format::Points | Format::Lines | Format::Triangles => (),
+            _ => bail!("Transform feedback can only capture points, lines or triangles"),
+        }
+
+        let query = get_value(0, |query| unsafe {
+            gl::GenQueries(1, query);
+        });
+
+        self.bind();
+        unsafe {
+            gl::BeginQuery(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN, query);
+            gl::BeginTransformFeedback(format.value());
+        }
+
+        Ok(TransformFeedback {
+            vao: self,
+            query,
+            ended: false,
+        })
+    }
+}
+
+impl<'a> TransformFeedback<'a> {
+    pub fn draw_arrays(&mut self, i0: GLuint, len: GLuint) {
+        self.vao.draw_arrays(i0, len);
+    }
+
+    pub fn draw_elements(&mut self, len: GLuint, ty: Primitive, i0: GLuint) {
+        self.vao.draw_elements(len, ty, i0);
+    }
+
+    /// Stops capturing until `resume` is called, draws made in between are not captured.
+    pub fn pause(&mut self) {
+        unsafe {
+            gl::PauseTransformFeedback();
+        }
+    }
+
+    pub fn resume(&mut self) {
+        unsafe {
+            gl::ResumeTransformFeedback();
+        }
+    }
+
+    /// Stops capturing and returns the number of primitives written to the buffers.
+    /// Waits for the draws to finish.
+    pub fn end(mut self) -> GLuint {
+        self.finish();
+        get_value(0, |written| unsafe {
+            gl::GetQueryObjectuiv(self.query, gl::QUERY_RESULT, written);
+        })
+    }
+
+    fn finish(&mut self) {
+        if !self.ended {
+            self.ended = true;
+            unsafe {
+                gl::EndTransformFeedback();
+                gl::EndQuery(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN);
+            }
+        }
+    }
+}
+
+impl<'a> Drop for TransformFeedback<'a> {
+    fn drop(&mut self) {
+        self.finish();
+        unsafe {
+            gl::DeleteQueries(1, &self.query);
+        }
+    }
+}
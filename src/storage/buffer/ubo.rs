@@ -0,0 +1,88 @@
+use gl::types::*;
+use std::marker::PhantomData;
+use super::{BufferType, BufferAcces, UniformBuffer, Std140};
+use crate::get_value;
+
+/// A buffer holding a single `T` with the std140 layout, for use as a uniform block.
+pub struct UboBuffer<T, Acces>
+where
+    T: Std140,
+    Acces: BufferAcces
+{
+    id: GLuint,
+    data: PhantomData<T>,
+    acces: PhantomData<Acces>,
+}
+
+impl<T, Acces> UboBuffer<T, Acces>
+where
+    T: Std140,
+    Acces: BufferAcces
+{
+    pub fn new(value: &T) -> UboBuffer<T, Acces> {
+        let id = get_value(0, |id| unsafe {
+            gl::GenBuffers(1, id);
+        });
+
+        let ubo = UboBuffer {
+            id,
+            data: PhantomData,
+            acces: PhantomData,
+        };
+
+        let data = value.to_std140();
+        ubo.bind();
+        unsafe {
+            gl::BufferData(
+                UniformBuffer::value(),
+                data.len() as GLsizeiptr,
+                data.as_ptr() as *const GLvoid,
+                Acces::value(),
+            );
+        }
+
+        ubo
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindBuffer(UniformBuffer::value(), self.id);
+        }
+    }
+
+    /// Binds the buffer to the uniform block binding point set with `Program::bind_uniform_block`.
+    pub fn bind_base(&self, binding: GLuint) {
+        unsafe {
+            gl::BindBufferBase(UniformBuffer::value(), binding, self.id);
+        }
+    }
+
+    pub fn update(&mut self, value: &T) {
+        let data = value.to_std140();
+        self.bind();
+        unsafe {
+            gl::BufferSubData(
+                UniformBuffer::value(),
+                0,
+                data.len() as GLsizeiptr,
+                data.as_ptr() as *const GLvoid
+            );
+        }
+    }
+}
+
+impl<T, Acces> Drop for UboBuffer<T, Acces>
+where
+    T: Std140,
+    Acces: BufferAcces
+{
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
\ No newline at end of file
@@ -1,18 +1,19 @@
 use gl::types::*;
-use super::{Primitive, Buffer, BufferData, BufferAcces, BufferType, Format};
+use super::{Primitive, Buffer, BufferData, BufferAcces, BufferType, Format, Attribute, AttributeKind};
 use crate::get_value;
 
 use anyhow::{Result, bail};
 
 use std::collections::HashMap;
 
-type AttributePoint = (GLuint, GLint, GLenum, GLboolean, GLsizei, GLuint);
+type AttributePoint = (GLuint, GLint, GLenum, AttributeKind, GLsizei, GLuint);
 
 pub struct Vao {
     id: GLuint,
-    format: Format,
+    pub(crate) format: Format,
     bindings: HashMap<GLuint, Vec<AttributePoint>>,
-    location_count: GLuint
+    location_count: GLuint,
+    patch_vertices: GLint
 }
 
 impl Vao {
@@ -23,9 +24,10 @@ impl Vao {
         
         let vao = Vao { 
             id,
-            format: format,
+            format,
             bindings: HashMap::new(),
-            location_count: locations
+            location_count: locations,
+            patch_vertices: 3
         };
 
         vao.bind();
@@ -49,6 +51,35 @@ impl Vao {
         location: GLuint,
         vbo: &mut Buffer<T, Kind, Acces>
     ) -> Result<Option<GLuint>>
+    where
+        T: Sized + BufferData,
+        Kind: BufferType,
+        Acces: BufferAcces
+    {
+        self.bind_vbo_with_divisor(location, vbo, 0)
+    }
+
+    /// Binds a buffer with one element per `divisor` instances instead of one per vertex.
+    pub fn bind_instance_vbo<T, Kind, Acces>(
+        &mut self,
+        location: GLuint,
+        vbo: &mut Buffer<T, Kind, Acces>,
+        divisor: GLuint
+    ) -> Result<Option<GLuint>>
+    where
+        T: Sized + BufferData,
+        Kind: BufferType,
+        Acces: BufferAcces
+    {
+        self.bind_vbo_with_divisor(location, vbo, divisor)
+    }
+
+    fn bind_vbo_with_divisor<T, Kind, Acces>(
+        &mut self,
+        location: GLuint,
+        vbo: &mut Buffer<T, Kind, Acces>,
+        divisor: GLuint
+    ) -> Result<Option<GLuint>>
     where
         T: Sized + BufferData,
         Kind: BufferType,
@@ -58,25 +89,24 @@ impl Vao {
 
         // Check if the buffer is already bound
         if let Some(bindings) = self.bindings.get(&vbo.id()) {
-            bound = bindings.len() == 0 || bindings[0].0 != location;
+            bound = bindings.is_empty() || bindings[0].0 != location;
             if bound {
                 // If it is apply the binding
-                self.rebind_vbo(vbo, bindings);
+                self.rebind_vbo(vbo, bindings, divisor);
             }
         }
         
         // If it is not create a new binding and apply it
         if !bound {
             // Generate binding
-            let prototype = T::prototype();
-            let bindings = self.generate_binding(
+            let bindings = self.generate_layout_binding(
                 std::mem::size_of::<T>() as GLuint,
                 location,
-                prototype
+                T::layout()
             )?;
             
             // Apply binding
-            self.rebind_vbo(vbo, &bindings);
+            self.rebind_vbo(vbo, &bindings, divisor);
             let bindings_len = bindings.len();
             self.bindings.insert(vbo.id(), bindings);
 
@@ -90,7 +120,8 @@ impl Vao {
     fn rebind_vbo<T, Kind, Acces>(
         &self,
         vbo: &mut Buffer<T, Kind, Acces>,
-        bindings: &Vec<AttributePoint>
+        bindings: &Vec<AttributePoint>,
+        divisor: GLuint
     ) 
     where
         T: Sized + BufferData,
@@ -100,16 +131,16 @@ impl Vao {
         // set vao and vbo as active
         self.bind();
         vbo.bind();
-        for (location, size, ty, norm, stride, offset) in bindings {
+        for (location, size, ty, kind, stride, offset) in bindings {
+            let offset = *offset as *const GLvoid;
             unsafe {
-                gl::VertexAttribPointer(
-                    *location,
-                    *size,
-                    *ty,
-                    *norm,
-                    *stride,
-                    *offset as *const GLvoid,
-                );
+                match kind {
+                    AttributeKind::Float => gl::VertexAttribPointer(*location, *size, *ty, gl::FALSE, *stride, offset),
+                    AttributeKind::Normalized => gl::VertexAttribPointer(*location, *size, *ty, gl::TRUE, *stride, offset),
+                    AttributeKind::Integer => gl::VertexAttribIPointer(*location, *size, *ty, *stride, offset),
+                    AttributeKind::Double => gl::VertexAttribLPointer(*location, *size, *ty, *stride, offset),
+                }
+                gl::VertexAttribDivisor(*location, divisor);
             }
         }
     }
@@ -121,7 +152,7 @@ impl Vao {
         prototype: Vec<(Primitive, GLuint)>
     ) -> Result<Vec<AttributePoint>> {
         // Check prototype size
-        let prototype_len = prototype.iter().fold(0, |acc, (ty, count)| acc + ty.size() * count);        
+        let prototype_len = prototype.iter().fold(0, |acc, (ty, count)| acc + ty.bytes(*count));        
 
         if object_size != prototype_len {
             bail!("Invalid prototype size of {} on object of size {}", prototype_len, object_size);
@@ -131,7 +162,7 @@ impl Vao {
         let mut offset: GLuint = 0;
         let mut bindings = Vec::new();
         for (id, (ty, count)) in prototype.iter().enumerate() {
-            if (id as GLuint) + location > self.location_count {
+            if (id as GLuint) + location >= self.location_count {
                 bail!("Ran out of shader variable location had {} but was making {}", self.location_count, (id as GLuint) + location);
             }
 
@@ -142,19 +173,72 @@ impl Vao {
                         (id as GLuint) + location,
                         *count as GLint,
                         ty.value(),
-                        gl::FALSE,
+                        AttributeKind::of(*ty),
                         prototype_len as gl::types::GLint,
                         offset
                     ));
                 }
             };
 
-            offset += ty.size() * count;
+            offset += ty.bytes(*count);
+        }
+        Ok(bindings)
+    }
+
+    /// Binds each attribute to the next location, at its offset in an object of `object_size` bytes.
+    pub fn generate_layout_binding(
+        &mut self,
+        object_size: GLuint,
+        location: GLuint,
+        layout: Vec<Attribute>
+    ) -> Result<Vec<AttributePoint>> {
+        let mut bindings = Vec::new();
+        for (id, attribute) in layout.iter().enumerate() {
+            if (id as GLuint) + location >= self.location_count {
+                bail!("Ran out of shader variable location had {} but was making {}", self.location_count, (id as GLuint) + location);
+            }
+
+            let end = attribute.offset + attribute.primitive.bytes(attribute.count);
+            if end > object_size {
+                bail!("Attribute ending at byte {} does not fit in object of size {}", end, object_size);
+            }
+
+            match attribute.primitive {
+                Primitive::Nothing => (),
+                _ => {
+                    bindings.push((
+                        (id as GLuint) + location,
+                        attribute.count as GLint,
+                        attribute.primitive.value(),
+                        attribute.kind,
+                        object_size as GLint,
+                        attribute.offset
+                    ));
+                }
+            };
         }
         Ok(bindings)
     }
 
+    /// Sets the number of vertices making up each patch when drawing with `Format::Patches`.
+    pub fn set_patch_vertices(&mut self, count: GLuint) {
+        self.patch_vertices = count as GLint;
+    }
+
+    pub fn patch_vertices(&self) -> GLuint {
+        self.patch_vertices as GLuint
+    }
+
+    pub(crate) fn apply_patch_vertices(&self) {
+        if let Format::Patches = self.format {
+            unsafe {
+                gl::PatchParameteri(gl::PATCH_VERTICES, self.patch_vertices);
+            }
+        }
+    }
+
     pub fn draw_arrays(&mut self, i0: GLuint, len: GLuint) {
+        self.apply_patch_vertices();
         unsafe {
             gl::DrawArrays(
                 self.format.value(),
@@ -165,6 +249,7 @@ impl Vao {
     }
 
     pub fn draw_elements(&mut self, len: GLuint, ty: Primitive, i0: GLuint) {
+        self.apply_patch_vertices();
         unsafe {
             gl::DrawElements(
                 self.format.value(),
@@ -174,6 +259,105 @@ impl Vao {
             );
         }
     }
+
+    /// Draws one range of vertices for each pair of `firsts` and `counts`.
+    /// Fails without drawing if the slices differ in length.
+    pub fn multi_draw_arrays(&mut self, firsts: &[GLint], counts: &[GLsizei]) -> Result<()> {
+        if firsts.len() != counts.len() {
+            bail!("multi_draw_arrays got {} first vertices but {} counts", firsts.len(), counts.len());
+        }
+        self.apply_patch_vertices();
+        unsafe {
+            gl::MultiDrawArrays(
+                self.format.value(),
+                firsts.as_ptr(),
+                counts.as_ptr(),
+                firsts.len() as GLsizei,
+            );
+        }
+        Ok(())
+    }
+
+    /// Draws `counts[i]` indices of type `ty` starting at the byte offset `offsets[i]` of the element buffer.
+    /// Fails without drawing if the slices differ in length.
+    pub fn multi_draw_elements(&mut self, counts: &[GLsizei], ty: Primitive, offsets: &[usize]) -> Result<()> {
+        if counts.len() != offsets.len() {
+            bail!("multi_draw_elements got {} counts but {} offsets", counts.len(), offsets.len());
+        }
+        let offsets: Vec<*const GLvoid> = offsets.iter().map(|offset| *offset as *const GLvoid).collect();
+        self.apply_patch_vertices();
+        unsafe {
+            gl::MultiDrawElements(
+                self.format.value(),
+                counts.as_ptr(),
+                ty.value(),
+                offsets.as_ptr(),
+                counts.len() as GLsizei,
+            );
+        }
+        Ok(())
+    }
+
+    pub fn draw_arrays_instanced(&mut self, i0: GLuint, len: GLuint, instances: GLuint) {
+        self.apply_patch_vertices();
+        unsafe {
+            gl::DrawArraysInstanced(
+                self.format.value(),
+                i0 as GLint,
+                len as GLint,
+                instances as GLsizei,
+            );
+        }
+    }
+
+    pub fn draw_elements_instanced(&mut self, len: GLuint, ty: Primitive, i0: GLuint, instances: GLuint) {
+        self.apply_patch_vertices();
+        unsafe {
+            gl::DrawElementsInstanced(
+                self.format.value(),
+                len as GLint,
+                ty.value(),
+                i0 as *const GLvoid,
+                instances as GLsizei,
+            );
+        }
+    }
+
+    /// Draws instances starting at `base_instance` in the instance buffers.
+    pub fn draw_arrays_instanced_base_instance(&mut self, i0: GLuint, len: GLuint, instances: GLuint, base_instance: GLuint) {
+        self.apply_patch_vertices();
+        unsafe {
+            gl::DrawArraysInstancedBaseInstance(
+                self.format.value(),
+                i0 as GLint,
+                len as GLint,
+                instances as GLsizei,
+                base_instance,
+            );
+        }
+    }
+
+    /// Draws instances starting at `base_instance` in the instance buffers.
+    pub fn draw_elements_instanced_base_instance(
+        &mut self,
+        len: GLuint,
+        ty: Primitive,
+        i0: GLuint,
+        instances: GLuint,
+        base_instance: GLuint
+    ) {
+        self.apply_patch_vertices();
+        unsafe {
+            gl::DrawElementsInstancedBaseInstance(
+                self.format.value(),
+                len as GLint,
+                ty.value(),
+                i0 as *const GLvoid,
+                instances as GLsizei,
+                base_instance,
+            );
+        }
+    }
 }
 
 
@@ -184,3 +368,44 @@ impl Drop for Vao {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testgl;
+
+    #[derive(BufferData)]
+    #[repr(C)]
+    struct Padded {
+        flag: u8,
+        position: [f32; 3],
+        weight: f64,
+    }
+
+    fn bindings(locations: GLuint, object_size: GLuint, location: GLuint) -> Result<Vec<AttributePoint>> {
+        testgl::load();
+        let mut vao = Vao::new(Format::Triangles, locations);
+        vao.generate_layout_binding(object_size, location, Padded::layout())
+    }
+
+    #[test]
+    fn layout_binding_uses_field_offsets() {
+        assert_eq!(std::mem::size_of::<Padded>(), 24);
+        assert_eq!(bindings(4, 24, 1).unwrap(), vec![
+            (1, 1, gl::UNSIGNED_BYTE, AttributeKind::Integer, 24, 0),
+            (2, 3, gl::FLOAT, AttributeKind::Float, 24, 4),
+            (3, 1, gl::DOUBLE, AttributeKind::Double, 24, 16),
+        ]);
+    }
+
+    #[test]
+    fn layout_binding_rejects_missing_locations() {
+        assert!(bindings(3, 24, 0).is_ok());
+        assert!(bindings(3, 24, 1).is_err());
+    }
+
+    #[test]
+    fn layout_binding_rejects_attributes_past_the_object() {
+        assert!(bindings(3, 20, 0).is_err());
+    }
+}
@@ -4,7 +4,7 @@ use super::*;
 
 pub struct WriteBufferMap <'a, T, Kind, Acces> 
 where
-    T: Sized + BufferData,
+    T: Sized,
     Kind: BufferType,
     Acces: BufferAcces
 {
@@ -14,7 +14,7 @@ where
 
 impl<T, Kind, Acces> Deref for WriteBufferMap<'_, T, Kind, Acces> 
 where
-    T: Sized + BufferData,
+    T: Sized,
     Kind: BufferType,
     Acces: BufferAcces
 {
@@ -27,7 +27,7 @@ where
 
 impl<T, Kind, Acces> DerefMut for WriteBufferMap<'_, T, Kind, Acces> 
 where
-    T: Sized + BufferData,
+    T: Sized,
     Kind: BufferType,
     Acces: BufferAcces
 {
@@ -38,7 +38,7 @@ where
 
 impl<T, Kind, Acces> Drop for WriteBufferMap<'_, T, Kind, Acces> 
 where
-    T: Sized + BufferData,
+    T: Sized,
     Kind: BufferType,
     Acces: BufferAcces
 {
@@ -1 +1 @@
-pub mod buffer;
\ No newline at end of file
+pub mod buffer;
@@ -0,0 +1,84 @@
+//! Stand-ins for the GL functions used by tests, which run without a GL context.
+//!
+//! Programs get increasing ids, vertex arrays are ignored, every uniform name resolves to location 0
+//! and float uniforms are recorded per thread together with the program they were set on.
+
+use gl::types::*;
+use std::cell::{Cell, RefCell};
+use std::os::raw::c_void;
+use std::sync::Once;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+static LOAD: Once = Once::new();
+static NEXT_PROGRAM: AtomicU32 = AtomicU32::new(1);
+
+thread_local! {
+    static CURRENT_PROGRAM: Cell<GLuint> = const { Cell::new(0) };
+    static UNIFORMS: RefCell<Vec<(GLuint, GLint, GLfloat)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Loads the stand-ins, the other GL functions stay unloaded and panic when called.
+pub(crate) fn load() {
+    LOAD.call_once(|| {
+        gl::CreateProgram::load_with(|_| create_program as *const c_void);
+        gl::DeleteProgram::load_with(|_| delete_program as *const c_void);
+        gl::UseProgram::load_with(|_| use_program as *const c_void);
+        gl::GetIntegerv::load_with(|_| get_integerv as *const c_void);
+        gl::GetUniformLocation::load_with(|_| get_uniform_location as *const c_void);
+        gl::Uniform1f::load_with(|_| uniform_1f as *const c_void);
+        gl::ProgramUniform1f::load_with(|_| program_uniform_1f as *const c_void);
+        gl::GenVertexArrays::load_with(|_| gen_vertex_arrays as *const c_void);
+        gl::DeleteVertexArrays::load_with(|_| delete_vertex_arrays as *const c_void);
+        gl::BindVertexArray::load_with(|_| bind_vertex_array as *const c_void);
+        gl::EnableVertexAttribArray::load_with(|_| enable_vertex_attrib_array as *const c_void);
+    });
+}
+
+/// Returns the `(program, location, value)` of every float uniform set on this thread since the last call.
+pub(crate) fn take_uniforms() -> Vec<(GLuint, GLint, GLfloat)> {
+    UNIFORMS.with(|uniforms| uniforms.take())
+}
+
+extern "system" fn create_program() -> GLuint {
+    NEXT_PROGRAM.fetch_add(1, Ordering::Relaxed)
+}
+
+extern "system" fn delete_program(_program: GLuint) {}
+
+extern "system" fn use_program(program: GLuint) {
+    CURRENT_PROGRAM.with(|current| current.set(program));
+}
+
+extern "system" fn get_integerv(name: GLenum, value: *mut GLint) {
+    assert_eq!(name, gl::CURRENT_PROGRAM, "only the current program can be queried");
+    unsafe {
+        *value = CURRENT_PROGRAM.with(|current| current.get()) as GLint;
+    }
+}
+
+extern "system" fn get_uniform_location(_program: GLuint, _name: *const GLchar) -> GLint {
+    0
+}
+
+extern "system" fn uniform_1f(location: GLint, value: GLfloat) {
+    let program = CURRENT_PROGRAM.with(|current| current.get());
+    UNIFORMS.with(|uniforms| uniforms.borrow_mut().push((program, location, value)));
+}
+
+extern "system" fn program_uniform_1f(program: GLuint, location: GLint, value: GLfloat) {
+    UNIFORMS.with(|uniforms| uniforms.borrow_mut().push((program, location, value)));
+}
+
+extern "system" fn gen_vertex_arrays(n: GLsizei, arrays: *mut GLuint) {
+    for i in 0..n as usize {
+        unsafe {
+            *arrays.add(i) = 1;
+        }
+    }
+}
+
+extern "system" fn delete_vertex_arrays(_n: GLsizei, _arrays: *const GLuint) {}
+
+extern "system" fn bind_vertex_array(_array: GLuint) {}
+
+extern "system" fn enable_vertex_attrib_array(_index: GLuint) {}